@@ -0,0 +1,82 @@
+use std::{ path::Path, sync::Mutex };
+use chrono::{ Duration, Utc };
+use rusqlite::{ params, Connection, OptionalExtension };
+use crate::{ error::{ Error, Result }, history::sha256_hex };
+
+/// AI filter verdicts from previous runs, so a re-run does not ask the LLM again.
+pub struct VerdictCache {
+    connection: Mutex<Connection>,
+    ttl: Duration,
+}
+
+impl VerdictCache {
+    /// Open or create the database, dropping verdicts older than `ttl_hours`.
+    pub fn open(path: &Path, ttl_hours: u32) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection: Connection = Connection::open(path).map_err(cache_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS verdicts (
+                    key TEXT PRIMARY KEY,
+                    relevant INTEGER NOT NULL,
+                    created_at TEXT NOT NULL
+                );"
+            )
+            .map_err(cache_error)?;
+
+        let cache: VerdictCache = VerdictCache {
+            connection: Mutex::new(connection),
+            ttl: Duration::hours(ttl_hours.into()),
+        };
+        cache.prune()?;
+        Ok(cache)
+    }
+
+    /// Cached verdict for `key`, `None` when missing or expired.
+    pub fn get(&self, key: &str) -> Result<Option<bool>> {
+        let cutoff: String = (Utc::now() - self.ttl).to_rfc3339();
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        connection
+            .query_row(
+                "SELECT relevant FROM verdicts WHERE key = ?1 AND datetime(created_at) >= datetime(?2)",
+                params![key, cutoff],
+                |row| row.get(0)
+            )
+            .optional()
+            .map_err(cache_error)
+    }
+
+    pub fn put(&self, key: &str, relevant: bool) -> Result<()> {
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO verdicts (key, relevant, created_at) VALUES (?1, ?2, ?3)",
+                params![key, relevant, Utc::now().to_rfc3339()]
+            )
+            .map_err(cache_error)?;
+        Ok(())
+    }
+
+    // Times are UTC; datetime() also reads the local offsets of older caches, comparing the
+    // strings would shift the TTL by the offset and jump with DST
+    fn prune(&self) -> Result<()> {
+        let cutoff: String = (Utc::now() - self.ttl).to_rfc3339();
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        connection
+            .execute("DELETE FROM verdicts WHERE datetime(created_at) < datetime(?1)", params![cutoff])
+            .map_err(cache_error)?;
+        Ok(())
+    }
+}
+
+/// Key of a verdict: the article text, the categories it was judged against and the models.
+pub fn verdict_key(title: &str, description: &str, categories: Option<&[String]>, model: &str) -> String {
+    let categories: String = categories.map(|c: &[String]| c.join("\u{1f}")).unwrap_or_default();
+    sha256_hex(&[title.trim(), description.trim(), &categories, model].join("\u{1e}"))
+}
+
+fn cache_error(e: rusqlite::Error) -> Error {
+    Error::Database(format!("verdict cache: {}", e))
+}
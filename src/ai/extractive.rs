@@ -0,0 +1,536 @@
+use std::collections::{ HashMap, HashSet };
+use async_trait::async_trait;
+use chrono::{ DateTime, FixedOffset };
+use crate::{
+    ai::script::{ BroadcastScript, NewsSegment },
+    checkpoint::RunDir,
+    config::Config,
+    console,
+    error::Result,
+    history::PreviousCoverage,
+    types::{ Article, Forecast },
+};
+use super::Summarizer;
+
+const MAX_STORIES: usize = 6;
+const SENTENCES_PER_STORY: usize = 2;
+// Title + description cosine similarity above which two articles are the same story
+const SAME_STORY_SIMILARITY: f64 = 0.3;
+// Sentences this close to one already picked add nothing
+const DUPLICATE_SENTENCE_SIMILARITY: f64 = 0.8;
+const MIN_SENTENCE_WORDS: usize = 5;
+const MAX_SENTENCE_WORDS: usize = 60;
+const DAMPING: f64 = 0.85;
+const TEXTRANK_ITERATIONS: usize = 30;
+
+const STOP_WORDS: &[&str] = &[
+    // English
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "have", "his", "how", "its", "may", "new", "now", "who", "did",
+    "get", "him", "she", "too", "use", "that", "with", "this", "from", "they", "will", "would",
+    "there", "their", "what", "about", "which", "when", "were", "been", "more", "than", "them",
+    "into", "also", "after", "said", "says", "over", "could", "other", "some", "these", "those",
+    // French
+    "les", "des", "une", "est", "pas", "par", "pour", "sur", "dans", "que", "qui", "aux", "avec",
+    "son", "ses", "sont", "ont", "mais", "plus", "cette", "ces", "nous", "vous", "ils", "elle",
+    "elles", "leur", "leurs", "été", "être", "avait", "fait", "comme", "tout", "tous", "entre",
+    "selon", "deux", "dont", "aussi", "après", "depuis", "sans", "sous", "encore", "même",
+];
+
+// Fixed lines of the locally built script
+struct Phrases {
+    intro: &'static str,
+    headlines: &'static str,
+    update: &'static str,
+    weather: &'static str,
+    weather_range: &'static str,
+    weather_elsewhere: &'static str,
+    weather_unavailable: &'static str,
+    no_news: &'static str,
+    outro: &'static str,
+}
+
+// Templates exist in French and English only, like the weather descriptions they embed;
+// any other language is spoken in English
+fn phrases(language_code: &str) -> Phrases {
+    match language_code {
+        "fr" =>
+            Phrases {
+                intro: "Bonjour et bienvenue sur Morioh-cho Radio, voici votre journal du matin.",
+                headlines: "Au sommaire ce matin, {count} sujets.",
+                update: "Du nouveau : {headline}",
+                weather: "À {city}, il fait actuellement {temperature} degrés, {conditions}.",
+                weather_range: "Aujourd'hui, les températures iront de {min} à {max} degrés.",
+                weather_elsewhere: "À {city}, {conditions}, de {min} à {max} degrés.",
+                weather_unavailable: "La météo n'est pas disponible ce matin, toutes nos excuses.",
+                no_news: "Pas d'information à vous donner aujourd'hui.",
+                outro: "Passez une excellente journée !",
+            },
+        _ =>
+            Phrases {
+                intro: "Good morning and welcome to Morioh-cho Radio, here is your morning news.",
+                headlines: "This morning, {count} stories.",
+                update: "Update: {headline}",
+                weather: "In {city}, it is currently {temperature} degrees, {conditions}.",
+                weather_range: "Today, temperatures will range from {min} to {max} degrees.",
+                weather_elsewhere: "In {city}, {conditions}, {min} to {max} degrees.",
+                weather_unavailable: "The weather report is not available this morning, sorry about that.",
+                no_news: "There is no information to share today.",
+                outro: "Have a great day!",
+            },
+    }
+}
+
+/// Summarizer that needs no LLM: stories are ranked by how many outlets cover them and how recent
+/// they are, and each is told with its most central sentences (TextRank over TF-IDF vectors).
+pub struct ExtractiveSummarizer;
+
+#[async_trait]
+impl Summarizer for ExtractiveSummarizer {
+    fn name(&self) -> &'static str {
+        "extractive"
+    }
+
+    async fn summarize(
+        &self,
+        weather: Option<&Forecast>,
+        elsewhere: &[Forecast],
+        articles: &[Article],
+        config: &Config,
+        _run: Option<&RunDir>
+    ) -> Result<BroadcastScript> {
+        Ok(extractive_resume(weather, elsewhere, articles, config))
+    }
+}
+
+/// Broadcast script built without any LLM: weather in a template and the top stories.
+/// Templates are French or English; other languages fall back to English.
+pub fn extractive_resume(
+    weather: Option<&Forecast>,
+    elsewhere: &[Forecast],
+    articles: &[Article],
+    config: &Config
+) -> BroadcastScript {
+    if !matches!(config.language_code(), "fr" | "en") {
+        console::warn(
+            format!("The extractive summary has no {} templates, using English", config.language)
+        );
+    }
+    let phrases: Phrases = phrases(config.language_code());
+
+    let mut weather: String = match weather {
+        Some(weather) => weather_segment(weather, &phrases, config.language_code()),
+        None => phrases.weather_unavailable.to_string(),
+    };
+    for other in elsewhere {
+        if let Some((min, max)) = other.today_range() {
+            weather.push(' ');
+            weather.push_str(
+                &phrases.weather_elsewhere
+                    .replace("{city}", &other.city)
+                    .replace(
+                        "{conditions}",
+                        other.current.get_weather_description(config.language_code())
+                    )
+                    .replace("{min}", &format!("{:.0}", min))
+                    .replace("{max}", &format!("{:.0}", max))
+            );
+        }
+    }
+
+    let news: Vec<NewsSegment> = top_stories(articles)
+        .iter()
+        .map(|story: &Vec<&Article>| story_segment(story, &phrases))
+        .collect();
+
+    let intro: String = if news.is_empty() {
+        format!("{} {}", phrases.intro, phrases.no_news)
+    } else {
+        format!(
+            "{} {}",
+            phrases.intro,
+            phrases.headlines.replace("{count}", &news.len().to_string())
+        )
+    };
+
+    BroadcastScript {
+        intro,
+        weather,
+        news,
+        outro: phrases.outro.to_string(),
+    }
+}
+
+fn weather_segment(weather: &Forecast, phrases: &Phrases, language_code: &str) -> String {
+    let mut text: String = phrases.weather
+        .replace("{city}", &weather.city)
+        .replace("{temperature}", &format!("{:.0}", weather.current.temperature))
+        .replace(
+            "{conditions}",
+            weather.current.get_weather_description(language_code)
+        );
+
+    if let Some((min, max)) = weather.today_range() {
+        text.push(' ');
+        text.push_str(
+            &phrases.weather_range
+                .replace("{min}", &format!("{:.0}", min))
+                .replace("{max}", &format!("{:.0}", max))
+        );
+    }
+    text
+}
+
+struct Story<'a> {
+    outlets: usize,
+    newest: Option<DateTime<FixedOffset>>,
+    articles: Vec<&'a Article>,
+}
+
+// Articles grouped by story, best first: covered by the most outlets, then most recent
+fn top_stories(articles: &[Article]) -> Vec<Vec<&Article>> {
+    let documents: Vec<Vec<String>> = articles
+        .iter()
+        .map(|a: &Article| tokenize(&format!("{} {}", a.title, a.description)))
+        .collect();
+    let idf: HashMap<String, f64> = inverse_document_frequency(&documents);
+    let vectors: Vec<HashMap<String, f64>> = documents
+        .iter()
+        .map(|tokens: &Vec<String>| tf_idf(tokens, &idf))
+        .collect();
+
+    // Greedy clustering: an article joins the first story where it resembles any article
+    let mut stories: Vec<Vec<usize>> = Vec::new();
+    for i in 0..articles.len() {
+        match stories.iter_mut().find(|story: &&mut Vec<usize>| {
+            story.iter().any(|&j| cosine(&vectors[j], &vectors[i]) >= SAME_STORY_SIMILARITY)
+        }) {
+            Some(story) => story.push(i),
+            None => stories.push(vec![i]),
+        }
+    }
+
+    let mut ranked: Vec<Story> = stories
+        .into_iter()
+        .map(|story: Vec<usize>| {
+            let articles: Vec<&Article> = story
+                .iter()
+                .map(|&i| &articles[i])
+                .collect();
+            let outlets: usize = articles
+                .iter()
+                .map(|a: &&Article| host(&a.source))
+                .collect::<HashSet<&str>>()
+                .len();
+            let newest: Option<DateTime<FixedOffset>> = articles
+                .iter()
+                .filter_map(|a: &&Article| parse_date(&a.date))
+                .max();
+            Story { outlets, newest, articles }
+        })
+        .collect();
+    ranked.sort_by(|a: &Story, b: &Story| b.outlets.cmp(&a.outlets).then(b.newest.cmp(&a.newest)));
+
+    ranked
+        .into_iter()
+        .take(MAX_STORIES)
+        .map(|story: Story| story.articles)
+        .collect()
+}
+
+fn story_segment(story: &[&Article], phrases: &Phrases) -> NewsSegment {
+    let headline: &str = story[0].title.trim();
+    let previous: Option<&PreviousCoverage> = story
+        .iter()
+        .find_map(|a: &&Article| a.update_on.as_ref());
+
+    let mut sentences: Vec<String> = Vec::new();
+    for article in story {
+        // Headline-only articles fall back to the description, when it says more than the title
+        let text: &str = if !article.content.trim().is_empty() {
+            &article.content
+        } else if article.description.trim() != article.title.trim() {
+            &article.description
+        } else {
+            ""
+        };
+        sentences.extend(split_sentences(text));
+    }
+    // For an update, what already aired is not news anymore
+    if let Some(previous) = previous {
+        sentences.retain(|s: &String| !previous.description.contains(s.as_str()));
+    }
+
+    let body: String = textrank(&sentences, SENTENCES_PER_STORY).join(" ");
+
+    let mut sources: Vec<String> = Vec::new();
+    for article in story {
+        if !article.source.is_empty() && !sources.contains(&article.source) {
+            sources.push(article.source.clone());
+        }
+    }
+
+    NewsSegment {
+        headline: match previous {
+            Some(_) => phrases.update.replace("{headline}", headline),
+            None => headline.to_string(),
+        },
+        body,
+        sources,
+    }
+}
+
+// The `count` most central sentences, in their original order
+fn textrank(sentences: &[String], count: usize) -> Vec<String> {
+    let documents: Vec<Vec<String>> = sentences
+        .iter()
+        .map(|s: &String| tokenize(s))
+        .collect();
+    let idf: HashMap<String, f64> = inverse_document_frequency(&documents);
+    let vectors: Vec<HashMap<String, f64>> = documents
+        .iter()
+        .map(|tokens: &Vec<String>| tf_idf(tokens, &idf))
+        .collect();
+
+    let n: usize = sentences.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let similarity: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| if i == j { 0.0 } else { cosine(&vectors[i], &vectors[j]) })
+                .collect()
+        })
+        .collect();
+    let out_weight: Vec<f64> = similarity
+        .iter()
+        .map(|row: &Vec<f64>| row.iter().sum())
+        .collect();
+
+    let mut scores: Vec<f64> = vec![1.0 / (n as f64); n];
+    for _ in 0..TEXTRANK_ITERATIONS {
+        scores = (0..n)
+            .map(|i| {
+                let incoming: f64 = (0..n)
+                    .filter(|&j| out_weight[j] > 0.0)
+                    .map(|j| (similarity[j][i] / out_weight[j]) * scores[j])
+                    .sum();
+                (1.0 - DAMPING) / (n as f64) + DAMPING * incoming
+            })
+            .collect();
+    }
+
+    // Stable sort: on equal scores the earlier sentence, usually the lead, wins
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+    let mut picked: Vec<usize> = Vec::new();
+    for i in order {
+        if picked.len() == count {
+            break;
+        }
+        let duplicate: bool = picked
+            .iter()
+            .any(|&p| similarity[p][i] >= DUPLICATE_SENTENCE_SIMILARITY);
+        if !duplicate {
+            picked.push(i);
+        }
+    }
+    picked.sort();
+    picked
+        .into_iter()
+        .map(|i| sentences[i].clone())
+        .collect()
+}
+
+// Sentences of a readable length; lines are boundaries too since extracted pages keep them
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let mut current: String = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            current.push(c);
+            let at_end: bool = matches!(c, '.' | '!' | '?') &&
+                chars.peek().is_none_or(|next: &char| next.is_whitespace());
+            if at_end {
+                sentences.push(std::mem::take(&mut current));
+            }
+        }
+        sentences.push(current);
+    }
+
+    sentences
+        .into_iter()
+        .map(|s: String| s.trim().to_string())
+        .filter(|s: &String| {
+            let words: usize = s.split_whitespace().count();
+            (MIN_SENTENCE_WORDS..=MAX_SENTENCE_WORDS).contains(&words)
+        })
+        .collect()
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|w: &str| w.to_lowercase())
+        .filter(|w: &String| w.chars().count() > 2 && !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+fn inverse_document_frequency(documents: &[Vec<String>]) -> HashMap<String, f64> {
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for tokens in documents {
+        for token in tokens.iter().map(String::as_str).collect::<HashSet<&str>>() {
+            *document_frequency.entry(token).or_default() += 1;
+        }
+    }
+    let n: f64 = documents.len() as f64;
+    document_frequency
+        .into_iter()
+        .map(|(token, df)| (token.to_string(), ((1.0 + n) / (1.0 + (df as f64))).ln() + 1.0))
+        .collect()
+}
+
+fn tf_idf(tokens: &[String], idf: &HashMap<String, f64>) -> HashMap<String, f64> {
+    let mut vector: HashMap<String, f64> = HashMap::new();
+    for token in tokens {
+        *vector.entry(token.clone()).or_default() += idf.get(token).copied().unwrap_or(1.0);
+    }
+    vector
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a
+        .iter()
+        .filter_map(|(token, weight)| b.get(token).map(|other: &f64| weight * other))
+        .sum();
+    let norm = |v: &HashMap<String, f64>| v.values().map(|w: &f64| w * w).sum::<f64>().sqrt();
+    let norms: f64 = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+// Outlet of an article URL, so two pages from the same site count once
+fn host(url: &str) -> &str {
+    let without_scheme: &str = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host: &str = without_scheme.split(['/', '?', '#']).next().unwrap_or_default();
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+// RSS dates are RFC 2822, Atom ones RFC 3339
+fn parse_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(language: &str) -> Config {
+        let value: serde_json::Value = serde_json::json!({
+            "news_sources": [],
+            "filter": { "categories": [], "banned": [] },
+            "city": "Annecy",
+            "api_key": "",
+            "api_url": "",
+            "language": language,
+        });
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn article(title: &str, content: &str, source: &str) -> Article {
+        Article {
+            title: title.into(),
+            content: content.into(),
+            source: source.into(),
+            date: String::new(),
+            description: title.into(),
+            update_on: None,
+        }
+    }
+
+    #[test]
+    fn ranks_the_central_sentence_first() {
+        let sentences: Vec<String> = [
+            "The rail strike stopped trains across the region on Monday morning.",
+            "Unions said the rail strike would go on until the trains timetable is withdrawn.",
+            "A local bakery won a prize for its croissants last year.",
+            "Commuters waited for trains as the rail strike entered its second day.",
+        ]
+            .map(String::from)
+            .to_vec();
+
+        let picked: Vec<String> = textrank(&sentences, 1);
+        assert_eq!(picked.len(), 1);
+        assert!(picked[0].contains("rail strike"), "picked {:?}", picked);
+        assert!(!textrank(&sentences, 3).iter().any(|s: &String| s.contains("bakery")));
+    }
+
+    #[test]
+    fn keeps_the_original_order_of_picked_sentences() {
+        let sentences: Vec<String> = [
+            "Storm warnings were issued for the lake shore this weekend.",
+            "The storm is expected to reach the lake shore on Saturday night.",
+            "Ferries on the lake will stay in port while the storm passes.",
+        ]
+            .map(String::from)
+            .to_vec();
+
+        let picked: Vec<String> = textrank(&sentences, 2);
+        let positions: Vec<usize> = picked
+            .iter()
+            .map(|p: &String| sentences.iter().position(|s: &String| s == p).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w: &[usize]| w[0] < w[1]), "order {:?}", positions);
+    }
+
+    #[test]
+    fn handles_short_inputs() {
+        assert!(textrank(&[], 2).is_empty());
+        assert_eq!(split_sentences("Too short. Also short!"), Vec::<String>::new());
+        let single: Vec<String> = vec!["Only one sentence is long enough here.".to_string()];
+        assert_eq!(textrank(&single, 2), single);
+
+        // A headline without any text gives an empty body, not a panic
+        let headline_only: Article = article("Lake closed to swimmers", "", "https://a.example/1");
+        let script: BroadcastScript = extractive_resume(None, &[], &[headline_only], &config("en"));
+        assert_eq!(script.news.len(), 1);
+        assert_eq!(script.news[0].headline, "Lake closed to swimmers");
+        assert!(script.news[0].body.is_empty());
+
+        let script: BroadcastScript = extractive_resume(None, &[], &[], &config("en"));
+        assert!(script.news.is_empty());
+        assert!(script.intro.ends_with(phrases("en").no_news));
+    }
+
+    #[test]
+    fn groups_the_same_story_from_several_outlets() {
+        let articles: Vec<Article> = vec![
+            article("Heatwave hits Annecy lake shore", "", "https://a.example/heat"),
+            article("Election results announced tonight", "", "https://a.example/vote"),
+            article("Annecy heatwave hits the lake shore", "", "https://www.b.example/heat"),
+        ];
+
+        let stories: Vec<Vec<&Article>> = top_stories(&articles);
+        assert_eq!(stories.len(), 2);
+        // Two outlets beat one
+        assert_eq!(stories[0].len(), 2);
+        assert_eq!(host(&stories[0][1].source), "b.example");
+    }
+
+    #[test]
+    fn falls_back_to_english_templates() {
+        let english: Phrases = phrases("en");
+        for language in ["german", "italian", "klingon"] {
+            let script: BroadcastScript = extractive_resume(None, &[], &[], &config(language));
+            assert!(script.intro.starts_with(english.intro), "{}: {}", language, script.intro);
+            assert_eq!(script.weather, english.weather_unavailable);
+            assert_eq!(script.outro, english.outro);
+        }
+
+        let french: BroadcastScript = extractive_resume(None, &[], &[], &config("french"));
+        assert_eq!(french.outro, phrases("fr").outro);
+    }
+}
@@ -1,39 +1,53 @@
-use reqwest::Client;
-use serde_json::{ json, Value };
-use std::sync::atomic::{ AtomicUsize, Ordering };
-use crate::config::{ Config, FilterConfig };
+use std::sync::{ atomic::{ AtomicUsize, Ordering }, Arc, Mutex };
+use async_trait::async_trait;
+use serde_json::json;
+use crate::{
+    config::FilterConfig,
+    console,
+    error::{ Error, Result },
+    filter::{ FilterStage, Verdict },
+    output::Degradation,
+    types::Article,
+};
+use super::{ cache::{ verdict_key, VerdictCache }, provider::{ ChatRequest, LlmProvider } };
 
 static MODEL_INDEX: AtomicUsize = AtomicUsize::new(0);
 
+// Available models array to bypass limit and speed up the filtering process
+const FILTER_MODELS: &[&str] = &[
+    "gemma2-9b-it",
+    "llama-3.1-70b-versatile",
+    "llama-3.2-11b-vision-preview",
+    "llama3-70b-8192",
+];
+
 pub async fn ai_filter(
     title: &str,
     description: &str,
-    config: &Config,
     filter_config: &FilterConfig,
-    client: &Client
-) -> Result<bool, Box<dyn std::error::Error>> {
+    llm: &dyn LlmProvider
+) -> Result<bool> {
     const MAX_RETRIES: u32 = 10;
+    // Failures other than rate limits, after which the LLM is taken as down
+    const MAX_FAILURES: u32 = 3;
     const TIMEOUT_SECS: u64 = 10;
+    let mut failures: u32 = 0;
     let categories: String = filter_config.categories.join(", ");
-
-    // Available models array to bypass limit and speed up the filtering process
-    let models: Vec<&str> = vec![
-        "gemma2-9b-it",
-        "llama-3.1-70b-versatile",
-        "llama-3.2-11b-vision-preview",
-        "llama3-70b-8192"
-    ];
+    let models: &[&str] = FILTER_MODELS;
 
     for attempt in 0..MAX_RETRIES {
         // Get current model and rotate to next one
         let current_index: usize = MODEL_INDEX.fetch_add(1, Ordering::SeqCst) % models.len();
         let current_model: &str = models[current_index];
 
-        let payload: Value =
-            json!({
-            "model": current_model,
-            "messages": [
-                {
+        let request: ChatRequest = ChatRequest {
+            model: Some(current_model.into()),
+            temperature: 0.1,
+            max_tokens: 1,
+            top_p: 0.1,
+            ..ChatRequest::new(
+                vec![
+                    json!({
                     "role": "assistant",
                     "content": format!(
                         "You are a news filter You MUST respond with ONLY 'true' or 'false'\n\
@@ -42,117 +56,189 @@ pub async fn ai_filter(
                         2. Answer 'false' if no match is found",
                         categories
                     )
-                },
-                {
+                }),
+                    json!({
                     "role": "user",
                     "content": format!(
                         "Evaluate if this content matches any category:\nTitle: {}\nDescription: {}",
                         title, description
                     )
-                }
-            ],
-            "temperature": 0.1,
-            "max_tokens": 1,
-            "top_p": 0.1,
-        });
+                })
+                ]
+            )
+        };
 
         match
             tokio::time::timeout(
                 std::time::Duration::from_secs(TIMEOUT_SECS),
-                client
-                    .post(&config.api_url)
-                    .header("Authorization", format!("Bearer {}", &config.api_key))
-                    .json(&payload)
-                    .send()
+                llm.chat(&request)
             ).await
         {
-            Ok(response_result) => {
-                match response_result {
-                    Ok(response) => {
-                        if !response.status().is_success() {
-                            let status: reqwest::StatusCode = response.status();
-                            let error_text: String = response.text().await?;
-                            #[cfg(debug_assertions)]
-                            eprintln!(
-                                "API error with model {} on attempt {}: {} - {}",
-                                current_model,
-                                attempt + 1,
-                                status,
-                                error_text
-                            );
-
-                            // If rate limited (429) or other 4xx error, try next model immediately
-                            if status.is_client_error() {
-                                continue;
-                            }
+            Ok(Ok(content)) => {
+                let content: &str = content.trim();
+                let is_relevant: bool = content == "true";
 
-                            if attempt == MAX_RETRIES - 1 {
-                                return Ok(false);
-                            }
-                            tokio::time::sleep(
-                                std::time::Duration::from_secs(2 * ((attempt + 1) as u64))
-                            ).await;
-                            continue;
-                        }
-
-                        match response.json::<Value>().await {
-                            Ok(body) => {
-                                let content: &str = body["choices"][0]["message"]["content"]
-                                    .as_str()
-                                    .unwrap_or("false")
-                                    .trim();
-
-                                let is_relevant: bool = content == "true";
-
-                                #[cfg(debug_assertions)]
-                                println!(
-                                    "Filter (using {}): '{}'\nResponse: '{}' -> {}",
-                                    current_model,
-                                    title,
-                                    content,
-                                    if is_relevant {
-                                        "✅"
-                                    } else {
-                                        "❌"
-                                    }
-                                );
-                                return Ok(is_relevant);
-                            }
-                            Err(e) => {
-                                #[cfg(debug_assertions)]
-                                eprintln!(
-                                    "JSON parse error on attempt {} with model {}: {}",
-                                    attempt + 1,
-                                    current_model,
-                                    e
-                                );
-                                if attempt == MAX_RETRIES - 1 {
-                                    return Ok(false);
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!(
-                            "Request error on attempt {} with model {}: {}",
-                            attempt + 1,
+                if cfg!(debug_assertions) || console::verbose() {
+                    console::status(
+                        format!(
+                            "Filter (using {}): '{}'\nResponse: '{}' -> {}",
                             current_model,
-                            e
-                        );
-                        if attempt == MAX_RETRIES - 1 {
-                            return Ok(false);
-                        }
-                    }
+                            title,
+                            content,
+                            if is_relevant {
+                                "✅"
+                            } else {
+                                "❌"
+                            }
+                        )
+                    );
                 }
+                return Ok(is_relevant);
+            }
+            // A rejected key fails every model the same way
+            Ok(Err(e @ Error::LlmAuth { .. })) => {
+                return Err(e);
+            }
+            Ok(Err(e @ (Error::LlmRateLimit { .. } | Error::LlmApi { .. }))) => {
+                if cfg!(debug_assertions) || console::verbose() {
+                    console::warn(
+                        format!("{} with model {} on attempt {}", e, current_model, attempt + 1)
+                    );
+                }
+
+                // If rate limited (429) or other 4xx error, try next model immediately
+                if let Error::LlmRateLimit { .. } | Error::LlmApi { status: 400..=499, .. } = e {
+                    continue;
+                }
+                failures += 1;
+            }
+            Ok(Err(e)) => {
+                console::warn(
+                    format!(
+                        "Request error on attempt {} with model {}: {}",
+                        attempt + 1,
+                        current_model,
+                        e
+                    )
+                );
+                failures += 1;
             }
             Err(_) => {
-                eprintln!("Timeout on attempt {} with model {}", attempt + 1, current_model);
-                if attempt == MAX_RETRIES - 1 {
-                    return Ok(false);
+                console::warn(
+                    format!("Timeout on attempt {} with model {}", attempt + 1, current_model)
+                );
+                failures += 1;
+            }
+        }
+        if failures >= MAX_FAILURES {
+            return Err(
+                Error::LlmRequest(format!("no model answered after {} failures", failures))
+            );
+        }
+        if attempt < MAX_RETRIES - 1 {
+            tokio::time::sleep(std::time::Duration::from_secs(2 * ((attempt + 1) as u64))).await;
+        }
+    }
+    // Not a verdict: the caller decides, and nothing gets cached
+    Err(Error::LlmRequest(format!("no model answered after {} attempts", MAX_RETRIES)))
+}
+
+/// Asks the LLM whether the article matches one of the configured categories.
+///
+/// Fails open: once the LLM can't give a verdict, it is not asked again and the remaining
+/// articles are kept unfiltered, which [`FilterStage::degradation`] reports.
+pub struct AiFilter {
+    filter: FilterConfig,
+    llm: Arc<dyn LlmProvider>,
+    cache: Option<Arc<VerdictCache>>,
+    // Why the LLM was given up on, and how many articles went through unfiltered since
+    outage: Mutex<Option<String>>,
+    unfiltered: AtomicUsize,
+}
+
+impl AiFilter {
+    pub fn new(filter: FilterConfig, llm: Arc<dyn LlmProvider>) -> Self {
+        AiFilter {
+            filter,
+            llm,
+            cache: None,
+            outage: Mutex::new(None),
+            unfiltered: AtomicUsize::new(0),
+        }
+    }
+
+    /// Reuse verdicts from previous runs; new ones are stored for the next.
+    pub fn with_cache(mut self, cache: Arc<VerdictCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    fn cache_key(&self, article: &Article) -> String {
+        let categories: Option<&[String]> = if self.filter.cache.invalidate_on_category_change {
+            Some(&self.filter.categories)
+        } else {
+            None
+        };
+        verdict_key(&article.title, &article.description, categories, &FILTER_MODELS.join(","))
+    }
+}
+
+#[async_trait]
+impl FilterStage for AiFilter {
+    fn name(&self) -> &str {
+        "AI"
+    }
+
+    fn show_progress(&self) -> bool {
+        true
+    }
+
+    async fn keep(&self, article: &Article, verdict: &mut Verdict) -> Result<bool> {
+        let key: Option<String> = self.cache.as_ref().map(|_| self.cache_key(article));
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            match cache.get(key) {
+                Ok(Some(is_relevant)) => {
+                    verdict.ai_relevant = Some(is_relevant);
+                    verdict.ai_cached = true;
+                    return Ok(is_relevant);
                 }
+                Ok(None) => {}
+                Err(e) => console::warn(e),
             }
         }
-        tokio::time::sleep(std::time::Duration::from_secs(2 * ((attempt + 1) as u64))).await;
+
+        if self.outage.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+            self.unfiltered.fetch_add(1, Ordering::SeqCst);
+            return Ok(true);
+        }
+
+        match
+            ai_filter(&article.title, &article.description, &self.filter, self.llm.as_ref()).await
+        {
+            Ok(is_relevant) => {
+                if let (Some(cache), Some(key)) = (&self.cache, &key) {
+                    if let Err(e) = cache.put(key, is_relevant) {
+                        console::warn(e);
+                    }
+                }
+                verdict.ai_relevant = Some(is_relevant);
+                Ok(is_relevant)
+            }
+            // No verdict: keep the article rather than air an empty show
+            Err(e) => {
+                console::warn(format!("AI filter disabled for this run: {}", e));
+                *self.outage.lock().unwrap_or_else(|e| e.into_inner()) = Some(e.to_string());
+                self.unfiltered.fetch_add(1, Ordering::SeqCst);
+                Ok(true)
+            }
+        }
+    }
+
+    fn degradation(&self) -> Option<Degradation> {
+        let reason: String = self.outage.lock().unwrap_or_else(|e| e.into_inner()).clone()?;
+        Some(Degradation::AiFilterSkipped {
+            articles: self.unfiltered.load(Ordering::SeqCst),
+            reason,
+        })
     }
-    Ok(false)
 }
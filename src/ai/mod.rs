@@ -1,2 +1,32 @@
+pub mod cache;
 pub mod resume;
 pub mod filter;
+pub mod extractive;
+pub mod provider;
+pub mod script;
+
+use async_trait::async_trait;
+use crate::{
+    checkpoint::RunDir,
+    config::Config,
+    error::Result,
+    types::{ Article, Forecast },
+};
+use script::BroadcastScript;
+
+/// Turns the weather and the kept articles into a broadcast script.
+#[async_trait]
+pub trait Summarizer: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// `elsewhere` holds the other configured locations, covered more briefly than `weather`.
+    /// Intermediate results go to `run` when given, and are reused from it on resume.
+    async fn summarize(
+        &self,
+        weather: Option<&Forecast>,
+        elsewhere: &[Forecast],
+        articles: &[Article],
+        config: &Config,
+        run: Option<&RunDir>
+    ) -> Result<BroadcastScript>;
+}
@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{ json, Value };
+use crate::{ config::Config, error::{ Error, Result } };
+
+/// A chat completion request, independent of the backend serving it.
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    /// Falls back to the provider's default model when `None`.
+    pub model: Option<String>,
+    pub messages: Vec<Value>,
+    pub temperature: f32,
+    pub max_tokens: u32,
+    pub top_p: f32,
+    /// Ask the backend to constrain the answer to a JSON object.
+    pub json_mode: bool,
+}
+
+impl ChatRequest {
+    pub fn new(messages: Vec<Value>) -> Self {
+        ChatRequest {
+            model: None,
+            messages,
+            temperature: 0.1,
+            max_tokens: 8000,
+            top_p: 0.3,
+            json_mode: false,
+        }
+    }
+}
+
+/// Anything able to answer chat completions, used by the AI filter and the summariser.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Returns the text content of the first choice.
+    async fn chat(&self, request: &ChatRequest) -> Result<String>;
+}
+
+/// OpenAI-compatible `/chat/completions` endpoint, Groq by default.
+pub struct OpenAiCompatible {
+    api_url: String,
+    api_key: String,
+    default_model: String,
+    client: Client,
+}
+
+impl OpenAiCompatible {
+    pub fn new(api_url: &str, api_key: &str, default_model: &str, client: Client) -> Self {
+        OpenAiCompatible {
+            api_url: api_url.into(),
+            api_key: api_key.into(),
+            default_model: default_model.into(),
+            client,
+        }
+    }
+
+    pub fn from_config(config: &Config, client: Client) -> Self {
+        Self::new(&config.api_url, &config.api_key, "llama-3.3-70b-versatile", client)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatible {
+    async fn chat(&self, request: &ChatRequest) -> Result<String> {
+        let mut payload: Value =
+            json!({
+            "model": request.model.as_deref().unwrap_or(&self.default_model),
+            "messages": request.messages,
+            "temperature": request.temperature,
+            "max_tokens": request.max_tokens,
+            "top_p": request.top_p,
+            "stream": false
+        });
+        if request.json_mode {
+            payload["response_format"] = json!({ "type": "json_object" });
+        }
+
+        let response: reqwest::Response = self.client
+            .post(&self.api_url)
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Bearer {}", &self.api_key))
+            .json(&payload)
+            .send().await
+            .map_err(|e| Error::LlmRequest(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status: reqwest::StatusCode = response.status();
+            let body: String = response.text().await.unwrap_or_default();
+            return Err(Error::from_llm_status(status, body));
+        }
+
+        let body: Value = response.json().await.map_err(|e| Error::LlmMalformed(e.to_string()))?;
+        let content: String = body["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| Error::LlmMalformed("no content in the first choice".into()))?
+            .to_string();
+
+        Ok(content)
+    }
+}
@@ -1,110 +1,285 @@
-use reqwest::Client;
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde::{ Deserialize, Serialize };
 use serde_json::{ json, Value };
-use crate::{ config::Config, types::WeatherResponse };
+use crate::{
+    checkpoint::RunDir,
+    config::Config,
+    console,
+    error::{ Error, Result },
+    fetch::forecast,
+    history::sha256_hex,
+    types::{ Article, Forecast },
+};
+use super::{ provider::{ ChatRequest, LlmProvider }, script::BroadcastScript, Summarizer };
+
+const SCRIPT_MAX_ATTEMPTS: u32 = 3;
+// Doubled after each rate-limited or failed request
+const SCRIPT_RETRY_DELAY_SECS: u64 = 2;
+
+const SCRIPT_SCHEMA: &str =
+    r#"{"intro": string, "weather": string, "news": [{"headline": string, "body": string, "sources": [string]}], "outro": string}"#;
 
 pub async fn ai_resume(
-    weather: &WeatherResponse,
+    weather: Option<&Forecast>,
+    elsewhere: &[Forecast],
     articles_text: &str,
-    client: &Client,
+    sources: &[&str],
+    llm: &dyn LlmProvider,
     config: &Config
-) -> Result<String, Box<dyn std::error::Error>> {
-    let weather_info: String = format!(
-        "Current weather at {} :\nTime: {}\nTemperature: {}°C\nConditions: {}\n{}",
-        weather.city,
-        weather.current_weather.time,
-        weather.current_weather.temperature,
-        weather.current_weather.get_weather_description(),
-        weather.get_day_forecast()
-    );
-
-    let payload: Value =
+) -> Result<BroadcastScript> {
+    let weather_info: String = match weather {
+        Some(weather) => {
+            let mut info: String = weather.get_detailed_forecast(config.language_code());
+            for line in forecast::outlook(weather, &config.weather.outlook, config.language_code()) {
+                info.push('\n');
+                info.push_str(&line);
+            }
+            if let Some(thresholds) = &config.weather.air_quality {
+                for line in weather.get_air_quality_alerts(thresholds, config.language_code()) {
+                    info.push('\n');
+                    info.push_str(&line);
+                }
+            }
+            info
+        }
+        None =>
+            format!(
+                "The weather service is unavailable for {} today. Briefly apologise that there is no weather report.",
+                config.location.display_name()
+            ),
+    };
+    let comparison: Vec<String> = forecast::comparison(elsewhere, config.language_code());
+    let weather_info: String = if comparison.is_empty() {
+        weather_info
+    } else {
+        format!("{}\nOther locations, compare briefly:\n{}", weather_info, comparison.join("\n"))
+    };
+
+    let mut messages: Vec<Value> = vec![
         json!({
-        "model": "llama-3.3-70b-versatile",
-        "messages": [
-            {
-                "role": "system",
-                "content": format!(
-                    "This is your host from Morioh-cho Radio, bringing you the latest news! You are a skilled journalist working for Morioh-cho Radio's morning news segment. Start with a good morning greeting, then present today's weather, followed by the news summary. End with 'Have a great day!'. If no articles are provided, mention there is no information today. You speak and write in {}.",
-                    &config.language
-                )
-            },
-            {
-                "role": "user",
-                "content": format!(
-                    "Format this summary as a radio show presentation with the following weather information:\n\nWeather Info:\n{}\n\nNews Summary:\n{}",
-                    weather_info,
-                    articles_text
-                )
+            "role": "system",
+            "content": format!(
+                "This is your host from Morioh-cho Radio, bringing you the latest news! You are a skilled journalist working for Morioh-cho Radio's morning news segment. Start with a good morning greeting in `intro`, present today's weather in `weather`, with practical advice on the alerts and the hours they apply to and the outlook when one is given, then a compact comparison with the other locations when there are any (for example 'sunny in Annecy, rain reaching Lyon by evening'), then one entry in `news` per story with a short headline, the spoken text as body and the source URLs it is based on. End with 'Have a great day!' in `outro`. Stories that are an update on one already aired are introduced as a follow-up and only tell what is new. If no articles are provided, leave `news` empty and mention there is no information today. You speak and write in {}. Respond with ONLY a JSON object matching this schema: {}",
+                &config.language,
+                SCRIPT_SCHEMA
+            )
+        }),
+        json!({
+            "role": "user",
+            "content": format!(
+                "Format this summary as a radio show presentation with the following weather information:\n\nWeather Info:\n{}\n\nNews Summary:\n{}",
+                weather_info,
+                articles_text
+            )
+        })
+    ];
+
+    // Last request error, only returned when the model never answered at all
+    let mut failure: Option<Error> = None;
+    let mut answered: bool = false;
+    for attempt in 0..SCRIPT_MAX_ATTEMPTS {
+        let request: ChatRequest = ChatRequest {
+            json_mode: true,
+            ..ChatRequest::new(messages.clone())
+        };
+        let raw: String = match llm.chat(&request).await {
+            Ok(raw) => raw,
+            // Asking again won't fix a rejected key
+            Err(e @ Error::LlmAuth { .. }) => {
+                return Err(e);
+            }
+            // Same prompt again after a pause, only a bad answer is worth re-prompting
+            Err(e) => {
+                console::warn(format!("Script request failed on attempt {}: {}", attempt + 1, e));
+                failure = Some(e);
+                if attempt + 1 < SCRIPT_MAX_ATTEMPTS {
+                    let delay: u64 = SCRIPT_RETRY_DELAY_SECS << attempt;
+                    tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+                }
+                continue;
             }
-        ],
-        "temperature": 0.1,
-        "max_tokens": 8000,
-        "top_p": 0.3,
-        "stream": false
-    });
-
-    let response: reqwest::Response = client
-        .post(&config.api_url)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", &config.api_key))
-        .json(&payload)
-        .send().await?;
-
-    if !response.status().is_success() {
-        let status: reqwest::StatusCode = response.status();
-        let error_text: String = response.text().await?;
-        return Err(format!("API error: {} - {}", status, error_text).into());
+        };
+        answered = true;
+
+        let errors: Vec<String> = match BroadcastScript::parse(&raw) {
+            Ok(mut script) => {
+                script.repair(sources);
+                let errors: Vec<String> = script.validate();
+                if errors.is_empty() {
+                    return Ok(script);
+                }
+                errors
+            }
+            Err(e) => vec![format!("invalid JSON: {}", e)],
+        };
+
+        if cfg!(debug_assertions) || console::verbose() {
+            console::warn(
+                format!("Invalid script on attempt {}: {}", attempt + 1, errors.join("; "))
+            );
+        }
+
+        // Show the model its own answer and what is wrong with it
+        messages.push(json!({ "role": "assistant", "content": raw }));
+        messages.push(
+            json!({
+            "role": "user",
+            "content": format!(
+                "Your answer does not match the schema: {}. Respond again with ONLY the corrected JSON object.",
+                errors.join("; ")
+            )
+        })
+        );
     }
 
-    let body: Value = response.json().await?;
-    let content: String = body["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or("Failed to extract content")?
-        .to_string();
+    if !answered {
+        if let Some(e) = failure {
+            return Err(e);
+        }
+    }
 
-    Ok(content)
+    // Nothing structured came back, keep the show on air with free text
+    console::warn("Falling back to a plain text script");
+    let plain_messages: Vec<Value> = vec![
+        json!({
+            "role": "system",
+            "content": format!(
+                "This is your host from Morioh-cho Radio, bringing you the latest news! You are a skilled journalist working for Morioh-cho Radio's morning news segment. Start with a good morning greeting, then present today's weather, followed by the news summary. End with 'Have a great day!'. If no articles are provided, mention there is no information today. You speak and write in {}.",
+                &config.language
+            )
+        }),
+        messages[1].clone()
+    ];
+    let text: String = llm.chat(&ChatRequest::new(plain_messages)).await?;
+    Ok(BroadcastScript::from_plain_text(&text))
 }
 
 pub async fn ai_resume_aggregate(
     partials: &str,
-    client: &Client,
-    config: &Config
-) -> Result<String, Box<dyn std::error::Error>> {
-    let payload: Value =
+    llm: &dyn LlmProvider
+) -> Result<String> {
+    let messages: Vec<Value> = vec![
+        json!({
+            "role": "system",
+            "content": "You are a master summarizer. Combine and condense these text  into one coherent summaries. Keep important details and remove redundancies. Keep the 'Source:' URL lines of every story you keep, and for 'Update on:' stories only what is new. Write in plain text, no markdown."
+        }),
         json!({
-        "model": "llama-3.3-70b-versatile",
-        "messages": [
-            {
-                "role": "system",
-                "content": "You are a master summarizer. Combine and condense these text  into one coherent summaries. Keep important details and remove redundancies. Write in plain text, no markdown."
-            },
-            {
-                "role": "user",
-                "content": format!("Partial text:\n\n{}", partials)
+            "role": "user",
+            "content": format!("Partial text:\n\n{}", partials)
+        })
+    ];
+
+    llm.chat(&ChatRequest::new(messages)).await
+}
+
+const MAX_CHUNK_SIZE: usize = 10000;
+
+/// Summarizer backed by the LLM: articles are condensed chunk by chunk, then scripted by `ai_resume`.
+pub struct LlmSummarizer {
+    llm: Arc<dyn LlmProvider>,
+}
+
+impl LlmSummarizer {
+    pub fn new(llm: Arc<dyn LlmProvider>) -> Self {
+        LlmSummarizer { llm }
+    }
+}
+
+#[async_trait]
+impl Summarizer for LlmSummarizer {
+    fn name(&self) -> &'static str {
+        "LLM"
+    }
+
+    async fn summarize(
+        &self,
+        weather: Option<&Forecast>,
+        elsewhere: &[Forecast],
+        articles: &[Article],
+        config: &Config,
+        run: Option<&RunDir>
+    ) -> Result<BroadcastScript> {
+        let articles_text: String = articles
+            .iter()
+            .map(|a: &Article| {
+                let update_on: String = match &a.update_on {
+                    Some(previous) =>
+                        format!(
+                            "Update on: already aired as \"{}\" ({}), tell only what is new\n",
+                            previous.title,
+                            previous.description
+                        ),
+                    None => String::new(),
+                };
+                format!(
+                    "Title: {}\nDate: {}\nSource: {}\n{}Description: {}\nContent: {}\n---\n",
+                    a.title,
+                    a.date,
+                    a.source,
+                    update_on,
+                    a.description,
+                    a.content
+                )
+            })
+            .collect::<String>();
+
+        let mut partial_summaries: Vec<String> = Vec::new();
+        for (i, chunk) in chunk_text(&articles_text, MAX_CHUNK_SIZE).iter().enumerate() {
+            let checkpoint_name: String = format!("summary-{:03}.json", i);
+            let input_hash: String = sha256_hex(chunk);
+
+            // Reused only for the very same input, the chunks move when the articles change
+            let saved: Option<ChunkSummary> = match run {
+                Some(run) => run.load(&checkpoint_name)?,
+                None => None,
+            };
+            if let Some(saved) = saved.filter(|s: &ChunkSummary| s.input_hash == input_hash) {
+                partial_summaries.push(saved.summary);
+                continue;
             }
-        ],
-        "temperature": 0.1,
-        "max_tokens": 8000,
-        "top_p": 0.3,
-        "stream": false
-    });
-
-    let response: reqwest::Response = client
-        .post(&config.api_url)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", &config.api_key))
-        .json(&payload)
-        .send().await?;
-
-    if !response.status().is_success() {
-        return Err(format!("API error: {} - {}", response.status(), response.text().await?).into());
+
+            let summary: String = ai_resume_aggregate(chunk, self.llm.as_ref()).await?;
+            if let Some(run) = run {
+                run.save(&checkpoint_name, &ChunkSummary { input_hash, summary: summary.clone() })?;
+            }
+            partial_summaries.push(summary);
+        }
+
+        let consolidated_summary: String = partial_summaries.join("\n");
+
+        let sources: Vec<&str> = articles
+            .iter()
+            .map(|a: &Article| a.source.as_str())
+            .collect();
+        ai_resume(
+            weather,
+            elsewhere,
+            &consolidated_summary,
+            &sources,
+            self.llm.as_ref(),
+            config
+        ).await
     }
+}
 
-    let body: Value = response.json::<serde_json::Value>().await?;
-    let content: String = body["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
+#[derive(Serialize, Deserialize)]
+struct ChunkSummary {
+    input_hash: String,
+    summary: String,
+}
 
-    Ok(content)
+// Split on char boundaries, French accents are more than one byte
+fn chunk_text(text: &str, max_len: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut start: usize = 0;
+    while start < text.len() {
+        let mut end: usize = (start + max_len).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        chunks.push(text[start..end].to_string());
+        start = end;
+    }
+    chunks
 }
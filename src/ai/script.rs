@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+use serde::{ Deserialize, Serialize };
+
+// Structured broadcast produced by the LLM, consumed by TTS, music cues and UI
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BroadcastScript {
+    pub intro: String,
+    pub weather: String,
+    pub news: Vec<NewsSegment>,
+    pub outro: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NewsSegment {
+    pub headline: String,
+    pub body: String,
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+impl BroadcastScript {
+    // Used when the model never produced valid JSON: the whole text is read as the intro
+    pub fn from_plain_text(text: &str) -> Self {
+        BroadcastScript {
+            intro: text.trim().to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Parse the raw model output, tolerating markdown fences and chatter around the object
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let start: usize = raw.find('{').ok_or("no JSON object in response")?;
+        let end: usize = raw.rfind('}').ok_or("unterminated JSON object in response")?;
+        if end < start {
+            return Err("no JSON object in response".into());
+        }
+        serde_json::from_str(&raw[start..=end]).map_err(|e| e.to_string())
+    }
+
+    // Fix what can be fixed without asking the model again
+    pub fn repair(&mut self, known_sources: &[&str]) {
+        self.intro = self.intro.trim().to_string();
+        self.weather = self.weather.trim().to_string();
+        self.outro = self.outro.trim().to_string();
+
+        for segment in &mut self.news {
+            segment.headline = segment.headline.trim().to_string();
+            segment.body = segment.body.trim().to_string();
+            // Only keep URLs that come from the fetched articles, the model tends to invent some
+            let mut seen: HashSet<String> = HashSet::new();
+            segment.sources = segment.sources
+                .iter()
+                .map(|url: &String| url.trim().to_string())
+                .filter(|url: &String| known_sources.contains(&url.as_str()))
+                .filter(|url: &String| seen.insert(url.clone()))
+                .collect();
+        }
+        self.news.retain(|s: &NewsSegment| !s.headline.is_empty() || !s.body.is_empty());
+    }
+
+    // Remaining schema violations, empty when the script is usable
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+        if self.intro.is_empty() {
+            errors.push("`intro` must not be empty".into());
+        }
+        if self.weather.is_empty() {
+            errors.push("`weather` must not be empty".into());
+        }
+        if self.outro.is_empty() {
+            errors.push("`outro` must not be empty".into());
+        }
+        for (i, segment) in self.news.iter().enumerate() {
+            if segment.headline.is_empty() {
+                errors.push(format!("`news[{}].headline` must not be empty", i));
+            }
+            if segment.body.is_empty() {
+                errors.push(format!("`news[{}].body` must not be empty", i));
+            }
+        }
+        errors
+    }
+
+    // Text of each spoken part in broadcast order, one news story per entry
+    pub fn spoken_segments(&self) -> Vec<String> {
+        let mut segments: Vec<String> = vec![self.intro.clone(), self.weather.clone()];
+        for segment in &self.news {
+            if segment.headline.is_empty() {
+                segments.push(segment.body.clone());
+            } else {
+                segments.push(
+                    format!("{}. {}", segment.headline.trim_end_matches('.'), segment.body)
+                );
+            }
+        }
+        segments.push(self.outro.clone());
+        segments.retain(|s: &String| !s.trim().is_empty());
+        segments
+    }
+
+    pub fn to_plain_text(&self) -> String {
+        let mut parts: Vec<&str> = vec![self.intro.as_str(), self.weather.as_str()];
+        for segment in &self.news {
+            parts.push(segment.headline.as_str());
+            parts.push(segment.body.as_str());
+        }
+        parts.push(self.outro.as_str());
+        parts
+            .into_iter()
+            .filter(|p: &&str| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
@@ -0,0 +1,111 @@
+use super::track::Track;
+
+// Integrated loudness following ITU-R BS.1770-4, in LUFS
+
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+}
+
+impl Biquad {
+    fn process(&self, input: &[f64]) -> Vec<f64> {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        input
+            .iter()
+            .map(|&x: &f64| {
+                let y: f64 =
+                    self.b[0] * x + self.b[1] * x1 + self.b[2] * x2 - self.a[1] * y1 - self.a[2] * y2;
+                x2 = x1;
+                x1 = x;
+                y2 = y1;
+                y1 = y;
+                y
+            })
+            .collect()
+    }
+}
+
+// Two-stage K-weighting filter, coefficients derived for any sample rate
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    // Stage 1: high shelf, +4 dB above ~1.5 kHz
+    let a: f64 = (10.0f64).powf(4.0 / 40.0);
+    let w0: f64 = (2.0 * std::f64::consts::PI * 1500.0) / sample_rate;
+    let alpha: f64 = w0.sin() / (2.0 * std::f64::consts::FRAC_1_SQRT_2);
+    let cos: f64 = w0.cos();
+    let a0: f64 = a + 1.0 - (a - 1.0) * cos + 2.0 * a.sqrt() * alpha;
+    let shelf: Biquad = Biquad {
+        b: [
+            (a * (a + 1.0 + (a - 1.0) * cos + 2.0 * a.sqrt() * alpha)) / a0,
+            (-2.0 * a * (a - 1.0 + (a + 1.0) * cos)) / a0,
+            (a * (a + 1.0 + (a - 1.0) * cos - 2.0 * a.sqrt() * alpha)) / a0,
+        ],
+        a: [1.0, (2.0 * (a - 1.0 - (a + 1.0) * cos)) / a0, (a + 1.0 - (a - 1.0) * cos - 2.0 * a.sqrt() * alpha) / a0],
+    };
+
+    // Stage 2: high pass at 38 Hz
+    let w0: f64 = (2.0 * std::f64::consts::PI * 38.0) / sample_rate;
+    let alpha: f64 = w0.sin() / (2.0 * 0.5);
+    let cos: f64 = w0.cos();
+    let a0: f64 = 1.0 + alpha;
+    let high_pass: Biquad = Biquad {
+        b: [(1.0 + cos) / 2.0 / a0, -(1.0 + cos) / a0, (1.0 + cos) / 2.0 / a0],
+        a: [1.0, (-2.0 * cos) / a0, (1.0 - alpha) / a0],
+    };
+
+    [shelf, high_pass]
+}
+
+pub fn integrated_loudness(track: &Track) -> Option<f64> {
+    let channels: usize = track.channels as usize;
+    let frames: usize = track.frames();
+    let sample_rate: f64 = track.sample_rate as f64;
+    let [shelf, high_pass] = k_weighting(sample_rate);
+
+    let weighted: Vec<Vec<f64>> = (0..channels)
+        .map(|c: usize| {
+            let channel: Vec<f64> = (0..frames)
+                .map(|i: usize| track.samples[i * channels + c] as f64)
+                .collect();
+            high_pass.process(&shelf.process(&channel))
+        })
+        .collect();
+
+    // 400 ms blocks with 75% overlap
+    let block: usize = (0.4 * sample_rate) as usize;
+    let step: usize = block / 4;
+    if block == 0 || frames < block {
+        return None;
+    }
+
+    let block_powers: Vec<f64> = (0..=(frames - block) / step)
+        .map(|b: usize| {
+            let start: usize = b * step;
+            weighted
+                .iter()
+                .map(|channel: &Vec<f64>| {
+                    channel[start..start + block].iter().map(|x: &f64| x * x).sum::<f64>() /
+                        (block as f64)
+                })
+                .sum()
+        })
+        .collect();
+
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let gated_mean = |threshold: f64| -> Option<f64> {
+        let kept: Vec<f64> = block_powers
+            .iter()
+            .copied()
+            .filter(|&p: &f64| p > 0.0 && loudness(p) > threshold)
+            .collect();
+        if kept.is_empty() {
+            None
+        } else {
+            Some(kept.iter().sum::<f64>() / (kept.len() as f64))
+        }
+    };
+
+    // Absolute gate at -70 LUFS, then relative gate 10 LU below that level
+    let absolute: f64 = gated_mean(-70.0)?;
+    let relative: f64 = gated_mean(loudness(absolute) - 10.0)?;
+    Some(loudness(relative))
+}
@@ -0,0 +1,163 @@
+pub mod loudness;
+pub mod track;
+
+use std::path::{ Path, PathBuf };
+use tokio::process::Command;
+use crate::{ config::AudioConfig, error::{ Error, Result } };
+use track::{ db_to_gain, Track };
+
+const SAMPLE_RATE: u32 = 44100;
+const CHANNELS: u16 = 2;
+// Time for the music bed to dip under or come back up from speech
+const DUCK_RAMP_MS: u32 = 250;
+// Leave headroom for lossy encoders
+const PEAK_CEILING_DB: f32 = -1.0;
+
+fn ms_to_frames(ms: u32) -> usize {
+    ((SAMPLE_RATE as u64) * (ms as u64) / 1000) as usize
+}
+
+fn load_optional(path: &Option<String>) -> Result<Option<Track>> {
+    match path {
+        Some(path) => Ok(Some(Track::load(Path::new(path))?.conform(SAMPLE_RATE, CHANNELS))),
+        None => Ok(None),
+    }
+}
+
+// Jingles around the speech, crossfaded segments, ducked music bed and loudness normalisation
+pub async fn assemble(
+    speech_segments: &[PathBuf],
+    audio: &AudioConfig,
+    output_dir: &Path
+) -> Result<PathBuf> {
+    let crossfade: usize = ms_to_frames(audio.crossfade_ms);
+    let intro: Option<Track> = load_optional(&audio.intro_jingle)?;
+    let outro: Option<Track> = load_optional(&audio.outro_jingle)?;
+    let bed: Option<Track> = load_optional(&audio.music_bed)?;
+
+    let mut mix: Track = Track::silence(SAMPLE_RATE, CHANNELS, 0);
+    let mut cursor: usize = 0;
+
+    if let Some(intro) = &intro {
+        mix.mix_in(intro, 0, 0, crossfade);
+        cursor = intro.frames().saturating_sub(crossfade);
+    }
+
+    let bed_start: usize = cursor;
+    let mut speech_ranges: Vec<(usize, usize)> = Vec::new();
+    for path in speech_segments {
+        let segment: Track = Track::load(path)?.conform(SAMPLE_RATE, CHANNELS);
+        let frames: usize = segment.frames();
+        let fade: usize = crossfade.min(frames / 2);
+        mix.mix_in(&segment, cursor, fade, fade);
+        speech_ranges.push((cursor, cursor + frames));
+        cursor = (cursor + frames).saturating_sub(fade);
+    }
+    let bed_end: usize = cursor + crossfade;
+
+    if let Some(outro) = &outro {
+        mix.mix_in(outro, cursor, crossfade, 0);
+    }
+
+    if let Some(bed) = &bed {
+        let looped: Track = duck_bed(bed, bed_end.saturating_sub(bed_start), bed_start, &speech_ranges, audio);
+        mix.mix_in(&looped, bed_start, crossfade, crossfade);
+    }
+
+    normalize(&mut mix, audio.target_lufs);
+
+    let output: PathBuf = output_dir.join(&audio.output);
+    let is_wav: bool = output
+        .extension()
+        .map(|e: &std::ffi::OsStr| e.eq_ignore_ascii_case("wav"))
+        .unwrap_or(false);
+    if is_wav {
+        mix.save(&output)?;
+    } else {
+        let wav: PathBuf = output.with_extension("mix.wav");
+        mix.save(&wav)?;
+        encode(&wav, &output).await?;
+        tokio::fs::remove_file(&wav).await?;
+    }
+
+    Ok(output)
+}
+
+// Loop the bed over `frames` and lower it wherever a speech range overlaps
+fn duck_bed(
+    bed: &Track,
+    frames: usize,
+    offset: usize,
+    speech_ranges: &[(usize, usize)],
+    audio: &AudioConfig
+) -> Track {
+    let channels: usize = CHANNELS as usize;
+    let ramp: usize = ms_to_frames(DUCK_RAMP_MS);
+    let full: f32 = db_to_gain(audio.bed_volume);
+    let ducked: f32 = db_to_gain(audio.ducked_volume);
+
+    let mut gains: Vec<f32> = vec![full; frames];
+    for &(start, end) in speech_ranges {
+        let start: usize = start.saturating_sub(offset);
+        let end: usize = end.saturating_sub(offset);
+        let from: usize = start.saturating_sub(ramp);
+        let to: usize = (end + ramp).min(frames);
+        for (i, gain) in gains.iter_mut().enumerate().take(to).skip(from) {
+            let target: f32 = if i < start {
+                full + (ducked - full) * (((i - from) as f32) / (ramp as f32))
+            } else if i >= end {
+                ducked + (full - ducked) * (((i - end) as f32) / (ramp as f32))
+            } else {
+                ducked
+            };
+            *gain = gain.min(target);
+        }
+    }
+
+    let bed_frames: usize = bed.frames();
+    let mut looped: Track = Track::silence(SAMPLE_RATE, CHANNELS, frames);
+    if bed_frames == 0 {
+        return looped;
+    }
+    for (i, gain) in gains.iter().enumerate() {
+        let source: usize = (i % bed_frames) * channels;
+        for c in 0..channels {
+            looped.samples[i * channels + c] = bed.samples[source + c] * gain;
+        }
+    }
+    looped
+}
+
+fn normalize(mix: &mut Track, target_lufs: f32) {
+    if let Some(measured) = loudness::integrated_loudness(mix) {
+        mix.apply_gain(db_to_gain(target_lufs - (measured as f32)));
+    }
+
+    // No true limiter, pull the whole mix down if the gain pushed peaks over the ceiling
+    let ceiling: f32 = db_to_gain(PEAK_CEILING_DB);
+    let peak: f32 = mix.peak();
+    if peak > ceiling {
+        mix.apply_gain(ceiling / peak);
+    }
+}
+
+async fn encode(input: &Path, output: &Path) -> Result<()> {
+    let result: std::process::Output = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-i")
+        .arg(input)
+        .arg(output)
+        .output().await
+        .map_err(|e| Error::Audio(format!("failed to start ffmpeg: {}", e)))?;
+
+    if !result.status.success() {
+        return Err(
+            Error::Audio(
+                format!("ffmpeg exited with {}: {}", result.status, String::from_utf8_lossy(&result.stderr).trim())
+            )
+        );
+    }
+    Ok(())
+}
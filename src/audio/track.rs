@@ -0,0 +1,141 @@
+use std::path::Path;
+use crate::error::{ Error, Result };
+
+// Interleaved PCM held as f32 in [-1.0, 1.0]
+#[derive(Clone)]
+pub struct Track {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<f32>,
+}
+
+impl Track {
+    pub fn silence(sample_rate: u32, channels: u16, frames: usize) -> Self {
+        Track {
+            sample_rate,
+            channels,
+            samples: vec![0.0; frames * (channels as usize)],
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let wav_error = |e: hound::Error| Error::Audio(format!("{}: {}", path.display(), e));
+        let mut reader: hound::WavReader<_> = hound::WavReader::open(path).map_err(wav_error)?;
+        let spec: hound::WavSpec = reader.spec();
+
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float =>
+                reader
+                    .samples::<f32>()
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(wav_error)?,
+            hound::SampleFormat::Int => {
+                let scale: f32 = (1u64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .samples::<i32>()
+                    .map(|s: std::result::Result<i32, hound::Error>| s.map(|s: i32| (s as f32) / scale))
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(wav_error)?
+            }
+        };
+
+        Ok(Track {
+            sample_rate: spec.sample_rate,
+            channels: spec.channels,
+            samples,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let spec: hound::WavSpec = hound::WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let wav_error = |e: hound::Error| Error::Audio(format!("{}: {}", path.display(), e));
+        let mut writer: hound::WavWriter<_> = hound::WavWriter::create(path, spec).map_err(wav_error)?;
+        for sample in &self.samples {
+            writer
+                .write_sample((sample.clamp(-1.0, 1.0) * (i16::MAX as f32)) as i16)
+                .map_err(wav_error)?;
+        }
+        writer.finalize().map_err(wav_error)
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len() / (self.channels as usize)
+    }
+
+    // Convert to the mix format: up/down-mix the channels, then linear resampling
+    pub fn conform(self, sample_rate: u32, channels: u16) -> Self {
+        let source_channels: usize = self.channels as usize;
+        let target_channels: usize = channels as usize;
+        let frames: usize = self.frames();
+
+        let mut remixed: Vec<f32> = Vec::with_capacity(frames * target_channels);
+        for frame in self.samples.chunks_exact(source_channels) {
+            for c in 0..target_channels {
+                remixed.push(if source_channels == 1 { frame[0] } else { frame[c.min(source_channels - 1)] });
+            }
+        }
+
+        if self.sample_rate == sample_rate || frames == 0 {
+            return Track { sample_rate, channels, samples: remixed };
+        }
+
+        let ratio: f64 = (self.sample_rate as f64) / (sample_rate as f64);
+        let target_frames: usize = ((frames as f64) / ratio).floor() as usize;
+        let mut resampled: Vec<f32> = Vec::with_capacity(target_frames * target_channels);
+        for i in 0..target_frames {
+            let position: f64 = (i as f64) * ratio;
+            let index: usize = position as usize;
+            let next: usize = (index + 1).min(frames - 1);
+            let t: f32 = (position - (index as f64)) as f32;
+            for c in 0..target_channels {
+                let a: f32 = remixed[index * target_channels + c];
+                let b: f32 = remixed[next * target_channels + c];
+                resampled.push(a + (b - a) * t);
+            }
+        }
+
+        Track { sample_rate, channels, samples: resampled }
+    }
+
+    // Add `other` (same format) starting at `at` frames, with linear fades at both ends
+    pub fn mix_in(&mut self, other: &Track, at: usize, fade_in: usize, fade_out: usize) {
+        let channels: usize = self.channels as usize;
+        let frames: usize = other.frames();
+        let needed: usize = (at + frames) * channels;
+        if self.samples.len() < needed {
+            self.samples.resize(needed, 0.0);
+        }
+
+        for i in 0..frames {
+            let mut gain: f32 = 1.0;
+            if i < fade_in {
+                gain *= (i as f32) / (fade_in as f32);
+            }
+            if frames - i <= fade_out {
+                gain *= ((frames - i) as f32) / (fade_out as f32);
+            }
+            for c in 0..channels {
+                self.samples[(at + i) * channels + c] += other.samples[i * channels + c] * gain;
+            }
+        }
+    }
+
+    pub fn apply_gain(&mut self, gain: f32) {
+        for sample in &mut self.samples {
+            *sample *= gain;
+        }
+    }
+
+    pub fn peak(&self) -> f32 {
+        self.samples.iter().fold(0.0, |peak: f32, s: &f32| peak.max(s.abs()))
+    }
+}
+
+pub fn db_to_gain(db: f32) -> f32 {
+    (10.0f32).powf(db / 20.0)
+}
@@ -0,0 +1,90 @@
+//! Per-run directory of stage outputs, so a failed run can resume where it stopped.
+
+use std::path::{ Path, PathBuf };
+use serde::{ de::DeserializeOwned, Serialize };
+use crate::error::{ Error, Result };
+
+/// `output_dir/runs/<id>`, holding one JSON file per completed step.
+pub struct RunDir {
+    id: String,
+    path: PathBuf,
+}
+
+impl RunDir {
+    /// A new run, named after the current time.
+    pub fn create(output_dir: &Path) -> Result<Self> {
+        let runs: PathBuf = output_dir.join("runs");
+        std::fs::create_dir_all(&runs)?;
+        let timestamp: String = chrono::Local::now().format("%Y-%m-%d-%H%M%S").to_string();
+        // Two profiles sharing `output_dir` may start in the same second
+        for attempt in 1.. {
+            let id: String = if attempt == 1 {
+                timestamp.clone()
+            } else {
+                format!("{}-{}", timestamp, attempt)
+            };
+            let path: PathBuf = runs.join(&id);
+            match std::fs::create_dir(&path) {
+                Ok(()) => {
+                    return Ok(RunDir { id, path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(e.into());
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    /// An earlier run, to resume from its last completed step.
+    pub fn open(output_dir: &Path, id: &str) -> Result<Self> {
+        let path: PathBuf = output_dir.join("runs").join(id);
+        if !path.is_dir() {
+            return Err(Error::Output(format!("no run '{}' in {}", id, output_dir.join("runs").display())));
+        }
+        Ok(RunDir { id: id.to_string(), path })
+    }
+
+    /// Every run in `output_dir`, newest first.
+    pub fn list(output_dir: &Path) -> Result<Vec<RunDir>> {
+        let runs: PathBuf = output_dir.join("runs");
+        if !runs.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut list: Vec<RunDir> = std::fs
+            ::read_dir(&runs)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| RunDir {
+                id: entry.file_name().to_string_lossy().into_owned(),
+                path: entry.path(),
+            })
+            .collect();
+        list.sort_by(|a: &RunDir, b: &RunDir| b.id.cmp(&a.id));
+        Ok(list)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Output of a completed step, `None` when the step has not completed yet.
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        let path: PathBuf = self.path.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content: String = std::fs::read_to_string(&path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Record a completed step; written aside then renamed, so a crash never leaves half a file.
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<()> {
+        let path: PathBuf = self.path.join(name);
+        let partial: PathBuf = path.with_extension("partial");
+        std::fs::write(&partial, serde_json::to_string_pretty(value)?)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use std::{ net::SocketAddr, path::PathBuf };
+use clap::{ Args, Parser, Subcommand };
+use moriocho_radio::config::SinkKind;
+
+#[derive(Parser, Debug)]
+#[command(name = "moriocho-radio", version, about = "Personalized morning news briefings")]
+pub struct Cli {
+    /// Config file, defaults to the sources.json bundled at build time
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Entry of the config's `profiles` object to apply
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Replaces `output_dir` from the config, stage files default to it
+    #[arg(long, global = true)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Show model answers and API errors (repeatable)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Only print errors and the final summary
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Don't wait for Enter before exiting
+    #[arg(long, global = true)]
+    pub non_interactive: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the whole pipeline (default)
+    Run {
+        #[command(flatten)]
+        sinks: SinkArgs,
+        /// Continue an interrupted run from its last completed step
+        #[arg(long, value_name = "RUN_ID")]
+        resume: Option<String>,
+    },
+    /// Fetch the news feeds into articles.json
+    Fetch {
+        /// Defaults to articles.json in the output directory
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Filter articles.json into kept.json
+    Filter(StageArgs),
+    /// Summarize kept.json into script.json
+    Summarize {
+        #[command(flatten)]
+        stage: StageArgs,
+        #[command(flatten)]
+        sinks: SinkArgs,
+    },
+    /// Run the profiles that have a `schedule`, at their times, until stopped
+    Daemon {
+        /// Defaults to daemon.log in the output directory
+        #[arg(long)]
+        log_file: Option<PathBuf>,
+    },
+    /// Serve the latest broadcast over HTTP and start new runs on request
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: SocketAddr,
+    },
+    /// Render script.json to audio
+    Speak {
+        /// Defaults to script.json in the output directory
+        #[arg(long)]
+        input: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug, Default)]
+pub struct StageArgs {
+    /// Stage input, defaults to the previous stage's file in the output directory
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+    /// Stage output, defaults to a file in the output directory
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct SinkArgs {
+    /// Output formats, replaces `sinks` from the config
+    #[arg(long, value_delimiter = ',')]
+    pub sinks: Option<Vec<SinkKind>>,
+}
+
+impl Cli {
+    pub fn verbosity(&self) -> u8 {
+        if self.quiet {
+            0
+        } else {
+            1 + self.verbose
+        }
+    }
+}
@@ -1,4 +1,8 @@
+use std::{ collections::HashMap, path::Path };
+use chrono::Weekday;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::{ Error, Result };
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Source {
@@ -8,19 +12,536 @@ pub struct Source {
 pub struct FilterConfig {
     pub categories: Vec<String>,
     pub banned: Vec<String>,
+    #[serde(default)]
+    pub cache: VerdictCacheConfig,
+}
+
+// AI filter verdicts kept between runs of the same morning
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerdictCacheConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    // SQLite database, relative to `output_dir`
+    #[serde(default = "default_verdict_cache_path")]
+    pub path: String,
+    #[serde(default = "default_verdict_cache_ttl_hours")]
+    pub ttl_hours: u32,
+    // Re-ask the LLM when `categories` change; off keeps verdicts across category tweaks
+    #[serde(default = "default_true")]
+    pub invalidate_on_category_change: bool,
+}
+
+impl Default for VerdictCacheConfig {
+    fn default() -> Self {
+        VerdictCacheConfig {
+            enabled: true,
+            path: default_verdict_cache_path(),
+            ttl_hours: default_verdict_cache_ttl_hours(),
+            invalidate_on_category_change: true,
+        }
+    }
+}
+
+fn default_verdict_cache_path() -> String {
+    "verdict_cache.sqlite".into()
+}
+
+fn default_verdict_cache_ttl_hours() -> u32 {
+    24
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsBackend {
+    Piper,
+    Espeak,
+    Openai,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TtsConfig {
+    pub backend: TtsBackend,
+    // Piper model path, espeak-ng voice or OpenAI voice; derived from `language` when missing
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default = "default_tts_speed")]
+    pub speed: f32,
+    // OpenAI-compatible backend only, defaults to the chat `api_url` host and `api_key`
+    #[serde(default)]
+    pub api_url: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default = "default_tts_model")]
+    pub model: String,
+    #[serde(default = "default_tts_output")]
+    pub output: String,
+}
+
+fn default_tts_speed() -> f32 {
+    1.0
+}
+
+fn default_tts_model() -> String {
+    "tts-1".into()
+}
+
+fn default_tts_output() -> String {
+    "broadcast.wav".into()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AudioConfig {
+    #[serde(default)]
+    pub intro_jingle: Option<String>,
+    #[serde(default)]
+    pub outro_jingle: Option<String>,
+    // Looped under the whole broadcast, between the jingles
+    #[serde(default)]
+    pub music_bed: Option<String>,
+    // Music bed gain in dB, without and with speech over it
+    #[serde(default = "default_bed_volume")]
+    pub bed_volume: f32,
+    #[serde(default = "default_ducked_volume")]
+    pub ducked_volume: f32,
+    #[serde(default = "default_crossfade_ms")]
+    pub crossfade_ms: u32,
+    #[serde(default = "default_target_lufs")]
+    pub target_lufs: f32,
+    // A non-WAV extension is encoded with ffmpeg
+    #[serde(default = "default_audio_output")]
+    pub output: String,
+}
+
+fn default_bed_volume() -> f32 {
+    -14.0
+}
+
+fn default_ducked_volume() -> f32 {
+    -28.0
+}
+
+fn default_crossfade_ms() -> u32 {
+    400
+}
+
+fn default_target_lufs() -> f32 {
+    -16.0
+}
+
+fn default_audio_output() -> String {
+    "broadcast.mp3".into()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PodcastConfig {
+    // Local directory holding `feed.xml` and the episode files
+    pub directory: String,
+    // Public URL `directory` is served from, episode enclosures are relative to it
+    pub base_url: String,
+    #[serde(default = "default_podcast_title")]
+    pub title: String,
+    #[serde(default = "default_podcast_description")]
+    pub description: String,
+    #[serde(default = "default_podcast_title")]
+    pub author: String,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default = "default_max_episodes")]
+    pub max_episodes: usize,
+}
+
+fn default_podcast_title() -> String {
+    "Morioh-cho Radio".into()
+}
+
+fn default_podcast_description() -> String {
+    "Your personalized morning news briefing".into()
+}
+
+fn default_max_episodes() -> usize {
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    Markdown,
+    Html,
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for SinkKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "markdown" | "md" => Ok(SinkKind::Markdown),
+            "html" => Ok(SinkKind::Html),
+            "text" | "txt" => Ok(SinkKind::Text),
+            "json" => Ok(SinkKind::Json),
+            other => Err(format!("Unknown sink '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryConfig {
+    // SQLite database, relative to `output_dir`
+    #[serde(default = "default_history_path")]
+    pub path: String,
+    // Air changed stories again as an "update on", otherwise anything already aired is dropped
+    #[serde(default = "default_true")]
+    pub updates: bool,
+    // Articles not seen for this long are forgotten
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_history_path() -> String {
+    "history.sqlite".into()
+}
+
+fn default_retention_days() -> u32 {
+    30
+}
+
+// When `daemon` runs the profile
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleConfig {
+    // e.g. "07:00 weekdays Europe/Paris", see `schedule::Schedule`
+    pub at: String,
+    // A run missed by more than this (machine asleep or off) is skipped, not caught up
+    #[serde(default = "default_catch_up_hours")]
+    pub catch_up_hours: u32,
+}
+
+fn default_catch_up_hours() -> u32 {
+    3
+}
+
+// Who writes the broadcast script
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SummarizerKind {
+    #[default]
+    Llm,
+    // Local TextRank summary, no LLM call at all (the AI filter is skipped too)
+    Extractive,
+}
+
+// What to do when a stage fails, instead of aborting the broadcast
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DegradationPolicy {
+    // Air the show without weather, with a spoken note
+    #[serde(default = "default_true")]
+    pub skip_weather: bool,
+    // Summarise from title and description when the article page can't be read
+    #[serde(default = "default_true")]
+    pub headline_only: bool,
+    // Build the script locally when the LLM is unreachable
+    #[serde(default = "default_true")]
+    pub extractive_fallback: bool,
+}
+
+impl Default for DegradationPolicy {
+    fn default() -> Self {
+        DegradationPolicy {
+            skip_weather: true,
+            headline_only: true,
+            extractive_fallback: true,
+        }
+    }
+}
+
+// Weather beyond today
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeatherConfig {
+    // Outlook added on each weekday of the location (`mon`, `fri`...); days not listed get none
+    #[serde(default = "default_outlook")]
+    pub outlook: HashMap<Weekday, Outlook>,
+    // Fetch air quality and pollen; `{}` keeps the default thresholds
+    #[serde(default)]
+    pub air_quality: Option<AirQualityConfig>,
+    #[serde(default)]
+    pub provider: WeatherBackend,
+    // Forecast JSON read by the `file` provider
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        WeatherConfig {
+            outlook: default_outlook(),
+            air_quality: None,
+            provider: WeatherBackend::default(),
+            file: None,
+        }
+    }
+}
+
+// The one HTTP client behind feeds, articles, weather, LLM and TTS requests
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpConfig {
+    #[serde(default = "default_user_agent")]
+    pub user_agent: String,
+    // "http://host:port"; the HTTP_PROXY/HTTPS_PROXY variables apply when unset
+    #[serde(default)]
+    pub proxy: Option<String>,
+    // Whole request, LLM answers included
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+    // PEM file trusted on top of the system roots, for proxies that inspect TLS
+    #[serde(default)]
+    pub ca_certificate: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            user_agent: default_user_agent(),
+            proxy: None,
+            timeout_secs: default_timeout_secs(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            pool_idle_timeout_secs: default_pool_idle_timeout_secs(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+            ca_certificate: None,
+        }
+    }
+}
+
+fn default_user_agent() -> String {
+    concat!("Mozilla/5.0 (compatible; moriocho-radio/", env!("CARGO_PKG_VERSION"), ")").to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    120
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    15
+}
+
+fn default_pool_max_idle_per_host() -> usize {
+    10
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum WeatherBackend {
+    #[default]
+    OpenMeteo,
+    // `weather.file`, no network
+    File,
+}
+
+// Levels from which the broadcast mentions air quality; below all of them it stays silent
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AirQualityConfig {
+    // European AQI, 60 is "poor"
+    #[serde(default = "default_european_aqi")]
+    pub european_aqi: f64,
+    // µg/m³
+    #[serde(default = "default_pm2_5")]
+    pub pm2_5: f64,
+    #[serde(default = "default_pm10")]
+    pub pm10: f64,
+    #[serde(default = "default_ozone")]
+    pub ozone: f64,
+    // grains/m³, levels at which most allergic people get symptoms
+    #[serde(default = "default_birch_pollen")]
+    pub birch_pollen: f64,
+    #[serde(default = "default_grass_pollen")]
+    pub grass_pollen: f64,
+}
+
+fn default_european_aqi() -> f64 {
+    60.0
+}
+
+fn default_pm2_5() -> f64 {
+    25.0
+}
+
+fn default_pm10() -> f64 {
+    50.0
+}
+
+fn default_ozone() -> f64 {
+    130.0
+}
+
+fn default_birch_pollen() -> f64 {
+    50.0
+}
+
+fn default_grass_pollen() -> f64 {
+    20.0
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outlook {
+    None,
+    Tomorrow,
+    // The coming Saturday and Sunday
+    Weekend,
+    // The next seven days
+    Week,
+}
+
+fn default_outlook() -> HashMap<Weekday, Outlook> {
+    HashMap::from([(Weekday::Fri, Outlook::Weekend), (Weekday::Mon, Outlook::Week)])
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_output_dir() -> String {
+    "output".into()
+}
+
+// Where the weather is for; written flat in the config, next to the other top-level keys
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Location {
+    pub city: String,
+    // Name read on air, `city` when missing
+    #[serde(default)]
+    pub name: Option<String>,
+    // Both set: no geocoding at all
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    // ISO 3166-1 alpha-2, so "Paris" with "FR" isn't Paris, Texas
+    #[serde(default)]
+    pub country_code: Option<String>,
+    // Region, state or province, for names repeated within a country
+    #[serde(default)]
+    pub admin1: Option<String>,
+}
+
+impl Location {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.city)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub news_sources: Vec<Source>,
     pub filter: FilterConfig,
-    pub city: String,
+    #[serde(flatten)]
+    pub location: Location,
+    // Other places, compared briefly after the main one
+    #[serde(default)]
+    pub locations: Vec<Location>,
         pub api_key: String,
     pub api_url: String,
     pub language: String,
+    #[serde(default)]
+    pub tts: Option<TtsConfig>,
+    #[serde(default)]
+    pub audio: Option<AudioConfig>,
+    #[serde(default)]
+    pub podcast: Option<PodcastConfig>,
+    #[serde(default)]
+    pub sinks: Vec<SinkKind>,
+    #[serde(default = "default_output_dir")]
+    pub output_dir: String,
+    #[serde(default)]
+    pub history: Option<HistoryConfig>,
+    #[serde(default)]
+    pub summarizer: SummarizerKind,
+    #[serde(default)]
+    pub schedule: Option<ScheduleConfig>,
+    #[serde(default)]
+    pub degradation: DegradationPolicy,
+    #[serde(default)]
+    pub weather: WeatherConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
-pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
-    const CONFIG_STR: &str = include_str!("../sources.json");
-    let config: Config = serde_json::from_str(CONFIG_STR)?;
+impl Config {
+    // ISO 639-1 code for `language`, which is written out in full for the LLM prompts
+    pub fn language_code(&self) -> &str {
+        match self.language.to_lowercase().as_str() {
+            "french" | "français" | "francais" | "fr" => "fr",
+            "german" | "deutsch" | "de" => "de",
+            "spanish" | "español" | "espanol" | "es" => "es",
+            "italian" | "italiano" | "it" => "it",
+            _ => "en",
+        }
+    }
+}
+
+// Read `path` (the bundled sources.json when None) and apply the named partial config
+// from its `profiles` object on top of it
+pub fn load_config_from(
+    path: Option<&Path>,
+    profile: Option<&str>
+) -> Result<Config> {
+    let mut value: Value = read_config_value(path)?;
+    if let Some(profile) = profile {
+        let overrides: Value = value
+            .get("profiles")
+            .and_then(|p: &Value| p.get(profile))
+            .cloned()
+            .ok_or_else(|| Error::Config(format!("unknown profile '{}'", profile)))?;
+        merge(&mut value, overrides);
+    }
+
+    let config: Config = serde_json::from_value(value).map_err(|e| Error::Config(e.to_string()))?;
     Ok(config)
 }
+
+/// Names of the entries of the config's `profiles` object.
+pub fn profile_names(path: Option<&Path>) -> Result<Vec<String>> {
+    let value: Value = read_config_value(path)?;
+    Ok(
+        value
+            .get("profiles")
+            .and_then(Value::as_object)
+            .map(|profiles| profiles.keys().cloned().collect())
+            .unwrap_or_default()
+    )
+}
+
+fn read_config_value(path: Option<&Path>) -> Result<Value> {
+    const CONFIG_STR: &str = include_str!("../sources.json");
+    let content: String = match path {
+        Some(path) =>
+            std::fs
+                ::read_to_string(path)
+                .map_err(|e| Error::Config(format!("failed to read {}: {}", path.display(), e)))?,
+        None => CONFIG_STR.to_string(),
+    };
+
+    serde_json::from_str(&content).map_err(|e| Error::Config(format!("invalid JSON: {}", e)))
+}
+
+// Objects are merged key by key, anything else is replaced
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overrides) => {
+            *base = overrides;
+        }
+    }
+}
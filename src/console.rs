@@ -0,0 +1,111 @@
+use std::{ fs::{ File, OpenOptions }, io::Write, path::Path, sync::Mutex };
+use std::sync::atomic::{ AtomicU8, Ordering };
+use indicatif::{ ProgressBar, ProgressDrawTarget, ProgressStyle };
+use serde::Serialize;
+
+// 0 = quiet, 1 = normal, 2+ = verbose
+static VERBOSITY: AtomicU8 = AtomicU8::new(1);
+
+// Set in daemon mode: messages go there, timestamped, instead of the terminal
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+// Last stage message and progress bar, for the `serve` status endpoint
+static CURRENT_STAGE: Mutex<Option<String>> = Mutex::new(None);
+static CURRENT_BAR: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// What the terminal currently shows: the last stage message and the progress bar, if any.
+#[derive(Debug, Serialize, Clone)]
+pub struct Progress {
+    pub stage: Option<String>,
+    pub position: Option<u64>,
+    pub length: Option<u64>,
+    pub elapsed_secs: Option<u64>,
+}
+
+pub fn progress() -> Progress {
+    let stage: Option<String> = CURRENT_STAGE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let bar: Option<ProgressBar> = CURRENT_BAR.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .filter(|bar: &ProgressBar| !bar.is_finished());
+    Progress {
+        stage,
+        position: bar.as_ref().map(ProgressBar::position),
+        length: bar.as_ref().and_then(ProgressBar::length),
+        elapsed_secs: bar.as_ref().map(|bar: &ProgressBar| bar.elapsed().as_secs()),
+    }
+}
+
+/// Forget the last stage, once a run is over.
+pub fn reset_progress() {
+    *CURRENT_STAGE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    *CURRENT_BAR.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+pub fn verbose() -> bool {
+    verbosity() > 1
+}
+
+/// Append every message to `path` from now on; progress bars are hidden.
+pub fn log_to_file(path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file: File = OpenOptions::new().create(true).append(true).open(path)?;
+    *LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+    Ok(())
+}
+
+fn logging_to_file() -> bool {
+    LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+fn emit(message: impl std::fmt::Display, to_stderr: bool) {
+    let mut log_file = LOG_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    match log_file.as_mut() {
+        Some(file) => {
+            let timestamp: String = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            let _ = writeln!(file, "[{}] {}", timestamp, message);
+        }
+        None if to_stderr => eprintln!("{}", message),
+        None => println!("{}", message),
+    }
+}
+
+// Stage progress messages, hidden with `--quiet`
+pub fn status(message: impl std::fmt::Display) {
+    *CURRENT_STAGE.lock().unwrap_or_else(|e| e.into_inner()) = Some(message.to_string());
+    if verbosity() > 0 {
+        emit(message, false);
+    }
+}
+
+// Recoverable failures, always shown
+pub fn warn(message: impl std::fmt::Display) {
+    emit(message, true);
+}
+
+pub fn progress_bar(len: usize) -> ProgressBar {
+    // Hidden bars still count, for `progress()`
+    let bar: ProgressBar = if verbosity() == 0 || logging_to_file() {
+        ProgressBar::with_draw_target(Some(len as u64), ProgressDrawTarget::hidden())
+    } else {
+        ProgressBar::new(len as u64).with_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%)"
+                )
+                .unwrap()
+        )
+    };
+    *CURRENT_BAR.lock().unwrap_or_else(|e| e.into_inner()) = Some(bar.clone());
+    bar
+}
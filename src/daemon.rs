@@ -0,0 +1,224 @@
+//! Runs each scheduled profile at its times, catching up on runs missed while asleep.
+
+use std::{ fs::{ File, TryLockError }, path::{ Path, PathBuf }, time::Duration as StdDuration };
+use chrono::{ DateTime, Duration, Local, Utc };
+use serde::{ Deserialize, Serialize };
+use crate::{
+    checkpoint::RunDir,
+    config::{ self, Config },
+    console,
+    error::{ Error, Result },
+    output::RunReport,
+    pipeline::Pipeline,
+    schedule::Schedule,
+};
+
+// The wall clock is checked at least this often, so a run missed during sleep starts soon
+// after wake-up: monotonic timers don't advance while the machine is suspended
+const POLL_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// Held while a profile runs; a second run of the same profile, from this process or
+/// another, fails to take it.
+pub struct ProfileLock {
+    _file: File,
+}
+
+pub fn lock_profile(output_dir: &Path, profile: Option<&str>) -> Result<ProfileLock> {
+    let name: &str = profile.unwrap_or("default");
+    let directory: PathBuf = output_dir.join("locks");
+    std::fs::create_dir_all(&directory)?;
+    let file: File = File::create(directory.join(format!("{}.lock", name)))?;
+    match file.try_lock() {
+        Ok(()) => Ok(ProfileLock { _file: file }),
+        Err(TryLockError::WouldBlock) =>
+            Err(Error::Output(format!("a run of profile '{}' is already in progress", name))),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+// Last scheduled time handled, run or skipped, kept across daemon restarts
+#[derive(Serialize, Deserialize, Default)]
+struct DaemonState {
+    last_scheduled: Option<DateTime<Utc>>,
+}
+
+/// Where the daemon reads profiles from, reloaded before every run so edits apply.
+pub struct DaemonConfig {
+    pub config_path: Option<PathBuf>,
+    /// Replaces `output_dir` of every profile.
+    pub output_dir: Option<String>,
+}
+
+impl DaemonConfig {
+    fn load(&self, profile: Option<&str>) -> Result<Config> {
+        let mut config: Config = config::load_config_from(self.config_path.as_deref(), profile)?;
+        if let Some(output_dir) = &self.output_dir {
+            config.output_dir = output_dir.clone();
+        }
+        Ok(config)
+    }
+
+    /// The base config and every profile that has a `schedule`.
+    pub fn scheduled_profiles(&self) -> Result<Vec<Option<String>>> {
+        let mut profiles: Vec<Option<String>> = Vec::new();
+        if self.load(None)?.schedule.is_some() {
+            profiles.push(None);
+        }
+        for name in config::profile_names(self.config_path.as_deref())? {
+            if self.load(Some(&name))?.schedule.is_some() {
+                profiles.push(Some(name));
+            }
+        }
+        Ok(profiles)
+    }
+}
+
+/// Run `profiles` on their schedules, forever.
+pub async fn run(daemon_config: &DaemonConfig, profiles: &[Option<String>]) -> Result<()> {
+    if profiles.is_empty() {
+        return Err(Error::Config("no profile has a `schedule`".into()));
+    }
+    // Fail now on a schedule typo rather than at 7 in the morning
+    for profile in profiles {
+        let config: Config = daemon_config.load(profile.as_deref())?;
+        let schedule: &config::ScheduleConfig = config.schedule
+            .as_ref()
+            .ok_or_else(|| Error::Config(format!("profile '{}' has no `schedule`", name(profile))))?;
+        schedule.at.parse::<Schedule>()?;
+        console::status(format!("[{}] Scheduled at {}", name(profile), schedule.at));
+    }
+
+    futures::future::join_all(
+        profiles.iter().map(|profile: &Option<String>| run_profile(daemon_config, profile.as_deref()))
+    ).await;
+    Ok(())
+}
+
+fn name(profile: &Option<String>) -> &str {
+    profile.as_deref().unwrap_or("default")
+}
+
+async fn run_profile(daemon_config: &DaemonConfig, profile: Option<&str>) {
+    let name: &str = profile.unwrap_or("default");
+    let mut state: Option<DaemonState> = None;
+
+    loop {
+        let config: Config = match daemon_config.load(profile) {
+            Ok(config) => config,
+            Err(e) => {
+                console::warn(format!("[{}] {}", name, e));
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+        };
+        let Some(schedule_config) = config.schedule.clone() else {
+            console::warn(format!("[{}] No `schedule` anymore, stopping", name));
+            return;
+        };
+        let schedule: Schedule = match schedule_config.at.parse() {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                console::warn(format!("[{}] {}", name, e));
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+        };
+
+        let state_path: PathBuf = Path::new(&config.output_dir)
+            .join("daemon")
+            .join(format!("{}.json", name));
+        // First start: only what is scheduled from now on
+        let state: &mut DaemonState = state.get_or_insert_with(|| {
+            load_state(&state_path).unwrap_or(DaemonState { last_scheduled: Some(Utc::now()) })
+        });
+
+        let now: DateTime<Utc> = Utc::now();
+        let due: Option<DateTime<Utc>> = schedule.due(state.last_scheduled, now);
+        if let Some(due) = due {
+            let due_local: String = due.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string();
+            if now - due <= Duration::hours(schedule_config.catch_up_hours.into()) {
+                console::status(format!("[{}] Starting the {} broadcast", name, due_local));
+                run_once(config, profile).await;
+            } else {
+                console::warn(
+                    format!(
+                        "[{}] Skipping the {} broadcast, missed by more than {}h",
+                        name,
+                        due_local,
+                        schedule_config.catch_up_hours
+                    )
+                );
+            }
+            state.last_scheduled = Some(due);
+            if let Err(e) = save_state(&state_path, state) {
+                console::warn(format!("[{}] Failed to save the daemon state: {}", name, e));
+            }
+            continue;
+        }
+
+        let wait: StdDuration = (schedule.next_after(now) - now)
+            .to_std()
+            .unwrap_or_default()
+            .min(POLL_INTERVAL);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+async fn run_once(config: Config, profile: Option<&str>) {
+    let name: &str = profile.unwrap_or("default");
+    let _lock: ProfileLock = match lock_profile(Path::new(&config.output_dir), profile) {
+        Ok(lock) => lock,
+        Err(e) => {
+            console::warn(format!("[{}] Skipped: {}", name, e));
+            return;
+        }
+    };
+
+    let run: RunDir = match RunDir::create(Path::new(&config.output_dir)) {
+        Ok(run) => run,
+        Err(e) => {
+            console::warn(format!("[{}] Run failed: {}", name, e.user_message()));
+            return;
+        }
+    };
+    let pipeline: Pipeline = match Pipeline::builder(config).build() {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            console::warn(format!("[{}] Run {} failed: {}", name, run.id(), e.user_message()));
+            return;
+        }
+    };
+    match pipeline.run_with(&run).await {
+        Ok(report) => {
+            log_report(name, &report);
+            match pipeline.speak(&report.script).await {
+                Some(audio) =>
+                    console::status(format!("[{}] Broadcast ready: {}", name, audio.display())),
+                None => console::status(format!("[{}] Broadcast ready (no audio)", name)),
+            }
+        }
+        Err(e) =>
+            console::warn(
+                format!("[{}] Run {} failed: {}", name, run.id(), e.user_message())
+            ),
+    }
+}
+
+fn log_report(name: &str, report: &RunReport) {
+    for degradation in &report.degradations {
+        console::warn(format!("[{}] Degraded: {}", name, degradation));
+    }
+}
+
+fn load_state(path: &Path) -> Option<DaemonState> {
+    let content: String = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_state(path: &Path, state: &DaemonState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use thiserror::Error;
+
+/// Everything that can go wrong in the pipeline.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("configuration error: {0}")]
+    Config(String),
+
+    #[error("failed to fetch feed {url}: {message}")]
+    FeedHttp {
+        url: String,
+        message: String,
+    },
+
+    #[error("failed to parse feed {url}: {message}")]
+    FeedParse {
+        url: String,
+        message: String,
+    },
+
+    #[error("city not found: {0}")]
+    Geocoding(String),
+
+    #[error("weather service error: {0}")]
+    Weather(String),
+
+    #[error("LLM API key rejected ({status}): {body}")]
+    LlmAuth {
+        status: u16,
+        body: String,
+    },
+
+    #[error("LLM rate limit reached: {body}")]
+    LlmRateLimit {
+        body: String,
+    },
+
+    #[error("LLM API error ({status}): {body}")]
+    LlmApi {
+        status: u16,
+        body: String,
+    },
+
+    #[error("LLM request failed: {0}")]
+    LlmRequest(String),
+
+    #[error("malformed LLM response: {0}")]
+    LlmMalformed(String),
+
+    #[error("failed to extract article {url}: {message}")]
+    Extraction {
+        url: String,
+        message: String,
+    },
+
+    #[error("speech rendering failed: {0}")]
+    Tts(String),
+
+    #[error("audio mixing failed: {0}")]
+    Audio(String),
+
+    #[error("local database error: {0}")]
+    Database(String),
+
+    #[error("output failed: {0}")]
+    Output(String),
+
+    #[error("HTTP server error: {0}")]
+    Server(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Map an unsuccessful LLM HTTP answer to the matching variant.
+    pub fn from_llm_status(status: reqwest::StatusCode, body: String) -> Self {
+        match status.as_u16() {
+            401 | 403 => Error::LlmAuth { status: status.as_u16(), body },
+            429 => Error::LlmRateLimit { body },
+            _ => Error::LlmApi { status: status.as_u16(), body },
+        }
+    }
+
+    /// Whether the LLM backend is the one failing.
+    pub fn is_llm(&self) -> bool {
+        matches!(
+            self,
+            Error::LlmAuth { .. } |
+                Error::LlmRateLimit { .. } |
+                Error::LlmApi { .. } |
+                Error::LlmRequest(_) |
+                Error::LlmMalformed(_)
+        )
+    }
+
+    /// Process exit code, one per failure family so scripts can tell them apart.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Config(_) => 2,
+            Error::FeedHttp { .. } => 3,
+            Error::FeedParse { .. } => 4,
+            Error::Geocoding(_) => 5,
+            Error::Weather(_) => 6,
+            Error::LlmAuth { .. } => 7,
+            Error::LlmRateLimit { .. } => 8,
+            Error::LlmApi { .. } | Error::LlmRequest(_) => 9,
+            Error::LlmMalformed(_) => 10,
+            Error::Extraction { .. } => 11,
+            Error::Tts(_) => 12,
+            Error::Audio(_) => 13,
+            Error::Output(_) | Error::Io(_) | Error::Json(_) => 14,
+            Error::Database(_) => 15,
+            Error::Server(_) => 16,
+        }
+    }
+
+    /// What to tell the user, with a hint on how to fix it when there is one.
+    pub fn user_message(&self) -> String {
+        let hint: &str = match self {
+            Error::Config(_) => "Check the config file and the --profile name.",
+            Error::FeedHttp { .. } => "Check your network connection or remove the feed.",
+            Error::FeedParse { .. } => "The feed is not valid RSS, check its URL.",
+            Error::Geocoding(_) =>
+                "Check `city`, `country_code` and `admin1` in the config, or set `latitude` and `longitude`.",
+            Error::Weather(_) => "Open-Meteo may be down, try again later.",
+            Error::LlmAuth { .. } => "Check `api_key` and `api_url` in the config.",
+            Error::LlmRateLimit { .. } => "Wait a few minutes before running again.",
+            Error::LlmApi { .. } | Error::LlmRequest(_) => "The LLM API is unreachable or failing.",
+            Error::LlmMalformed(_) => "The model answered something unusable, try again.",
+            Error::Extraction { .. } => "The article page could not be read.",
+            Error::Tts(_) => "Check the `tts` section of the config and that the engine is installed.",
+            Error::Audio(_) => "Check the jingle and music files in the `audio` section.",
+            Error::Output(_) | Error::Io(_) | Error::Json(_) => "Check the output directory and stage files.",
+            Error::Database(_) =>
+                "Check the `history` and `filter.cache` paths, or delete the database to start over.",
+            Error::Server(_) => "Check the --listen address, the port may already be in use.",
+        };
+        format!("{}\n{}", self, hint)
+    }
+}
@@ -0,0 +1,436 @@
+//! Splits the local day into morning, afternoon and evening for the weather segment, and
+//! looks further ahead on the days the config asks for an outlook.
+
+use std::collections::HashMap;
+use chrono::{ Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday };
+use crate::{ config::Outlook, types::Forecast };
+use super::{ weather::{ spoken_time, LIKELY_PRECIPITATION }, wmo };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Morning,
+    Afternoon,
+    Evening,
+}
+
+impl Period {
+    const ALL: [Period; 3] = [Period::Morning, Period::Afternoon, Period::Evening];
+
+    /// Local hours covered, end excluded.
+    pub fn hours(self) -> std::ops::Range<u32> {
+        match self {
+            Period::Morning => 6..12,
+            Period::Afternoon => 12..18,
+            Period::Evening => 18..24,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Period::Morning => "Morning",
+            Period::Afternoon => "Afternoon",
+            Period::Evening => "Evening",
+        }
+    }
+
+    // How a change starting in this period is introduced
+    fn when(self) -> &'static str {
+        match self {
+            Period::Morning => "in the morning",
+            Period::Afternoon => "after noon",
+            Period::Evening => "in the evening",
+        }
+    }
+}
+
+/// What one period of the day looks like.
+#[derive(Debug, Clone)]
+pub struct DayPart {
+    pub period: Period,
+    /// WMO code of the condition that lasts the most hours.
+    pub weathercode: u8,
+    pub min: f64,
+    pub max: f64,
+    /// Highest precipitation probability, in %.
+    pub rain_chance: Option<f64>,
+}
+
+impl DayPart {
+    /// Dominant condition in `language_code`.
+    pub fn condition(&self, language_code: &str) -> &'static str {
+        wmo::describe(self.weathercode, language_code)
+    }
+
+    // Precipitation reported, or rain more likely than not
+    fn is_wet(&self) -> bool {
+        wmo::is_precipitation(self.weathercode) ||
+            self.rain_chance.is_some_and(|p: f64| p >= LIKELY_PRECIPITATION)
+    }
+}
+
+/// Periods of the local day that are not over yet, from the hourly forecast.
+pub fn day_parts(weather: &Forecast) -> Vec<DayPart> {
+    let Some(now) = weather.local_now() else {
+        return Vec::new();
+    };
+    let hours: Vec<(NaiveDateTime, usize)> = weather
+        .today_hours()
+        .into_iter()
+        .filter_map(|i: usize| weather.local_time(&weather.hourly.time[i]).map(|t| (t, i)))
+        // The hour in progress still counts
+        .filter(|(time, _)| time.hour() >= now.hour())
+        .collect();
+
+    Period::ALL.iter()
+        .filter_map(|period: &Period| {
+            let indices: Vec<usize> = hours
+                .iter()
+                .filter(|(time, _)| period.hours().contains(&time.hour()))
+                .map(|(_, i)| *i)
+                .collect();
+            day_part(weather, *period, &indices)
+        })
+        .collect()
+}
+
+fn day_part(weather: &Forecast, period: Period, indices: &[usize]) -> Option<DayPart> {
+    let hourly = &weather.hourly;
+    let temperatures: Vec<f64> = indices
+        .iter()
+        .filter_map(|i: &usize| hourly.temperature.get(*i).copied())
+        .collect();
+    if temperatures.is_empty() {
+        return None;
+    }
+
+    // Hours per description, ties going to the more severe code
+    let codes: Vec<u8> = indices
+        .iter()
+        .filter_map(|i: &usize| hourly.weather_code.get(*i).copied().flatten())
+        .collect();
+    let weathercode: u8 = codes
+        .iter()
+        .map(|code: &u8| {
+            let description: &str = wmo::describe(*code, "en");
+            let count: usize = codes
+                .iter()
+                .filter(|other: &&u8| wmo::describe(**other, "en") == description)
+                .count();
+            (count, *code)
+        })
+        .max()
+        .map_or(weather.current.weather_code, |(_, code)| code);
+
+    let rain_chance: Option<f64> = indices
+        .iter()
+        .filter_map(|i: &usize| hourly.precipitation_chance.get(*i).copied().flatten())
+        .reduce(f64::max);
+
+    Some(DayPart {
+        period,
+        weathercode,
+        min: temperatures.iter().copied().fold(f64::INFINITY, f64::min),
+        max: temperatures.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        rain_chance,
+    })
+}
+
+/// One line per period, then the notable changes between them; hours are written the way
+/// they are said in `language_code`.
+pub fn narrative(weather: &Forecast, language_code: &str) -> Vec<String> {
+    let spoken = |hour: u32| {
+        spoken_time(NaiveTime::from_hms_opt(hour % 24, 0, 0).unwrap_or_default(), language_code)
+    };
+    let parts: Vec<DayPart> = day_parts(weather);
+    let mut lines: Vec<String> = parts
+        .iter()
+        .map(|part: &DayPart| {
+            let hours: std::ops::Range<u32> = part.period.hours();
+            let mut line: String = format!(
+                "{} ({} to {}): {}, {:.0}°C to {:.0}°C",
+                part.period.name(),
+                spoken(hours.start),
+                spoken(hours.end),
+                part.condition(language_code),
+                part.min,
+                part.max
+            );
+            if let Some(chance) = part.rain_chance {
+                line.push_str(&format!(", {:.0}% chance of rain", chance));
+            }
+            line
+        })
+        .collect();
+
+    let changes: Vec<String> = parts
+        .windows(2)
+        .filter_map(|pair: &[DayPart]| change(&pair[0], &pair[1], language_code))
+        .collect();
+    if !changes.is_empty() {
+        lines.push(format!("Changes: {}", changes.join("; ")));
+    }
+    lines
+}
+
+fn change(before: &DayPart, after: &DayPart, language_code: &str) -> Option<String> {
+    let when: &str = after.period.when();
+    match (before.is_wet(), after.is_wet()) {
+        (true, false) => Some(format!("clearing up {}", when)),
+        (false, true) => {
+            let what: &str = if wmo::is_precipitation(after.weathercode) {
+                after.condition(language_code)
+            } else {
+                "rain"
+            };
+            Some(format!("{} arriving {}", what, when))
+        }
+        (false, false) if before.condition("en") != after.condition("en") =>
+            Some(format!("turning to {} {}", after.condition(language_code), when)),
+        _ => None,
+    }
+}
+
+/// One compact line per location, to compare them in a sentence or two: the condition for
+/// the rest of the day, the temperature range and the first notable change.
+pub fn comparison(weathers: &[Forecast], language_code: &str) -> Vec<String> {
+    weathers
+        .iter()
+        .map(|weather: &Forecast| {
+            let parts: Vec<DayPart> = day_parts(weather);
+            let condition: &str = match parts.first() {
+                Some(part) => part.condition(language_code),
+                None => weather.current.get_weather_description(language_code),
+            };
+            let mut line: String = format!("- {}: {}", weather.city, condition);
+            if let Some((min, max)) = weather.today_range() {
+                line.push_str(&format!(", {:.0}°C to {:.0}°C", min, max));
+            }
+            if
+                let Some(first) = parts
+                    .windows(2)
+                    .find_map(|pair: &[DayPart]| change(&pair[0], &pair[1], language_code))
+            {
+                line.push_str(&format!(", {}", first));
+            }
+            line
+        })
+        .collect()
+}
+
+/// The outlook `rules` set for the location's weekday, one line per day after a heading;
+/// empty when there is none.
+pub fn outlook(
+    weather: &Forecast,
+    rules: &HashMap<Weekday, Outlook>,
+    language_code: &str
+) -> Vec<String> {
+    let Some(today) = weather.local_now().map(|now: NaiveDateTime| now.date()) else {
+        return Vec::new();
+    };
+    let following = (1..=7).filter_map(|offset| today.checked_add_signed(Duration::days(offset)));
+    let (heading, days): (&str, Vec<NaiveDate>) = match rules.get(&today.weekday()) {
+        None | Some(Outlook::None) => {
+            return Vec::new();
+        }
+        Some(Outlook::Tomorrow) => ("Tomorrow", following.take(1).collect()),
+        Some(Outlook::Weekend) =>
+            (
+                "This weekend",
+                following
+                    .filter(|date: &NaiveDate| date.weekday().num_days_from_monday() >= 5)
+                    .take(2)
+                    .collect(),
+            ),
+        Some(Outlook::Week) => ("The week ahead", following.collect()),
+    };
+
+    let daily = &weather.daily;
+    let at = |series: &[Option<f64>], i: usize| series.get(i).copied().flatten();
+    let lines: Vec<String> = days
+        .iter()
+        .filter_map(|date: &NaiveDate| {
+            let day: String = date.format("%Y-%m-%d").to_string();
+            let i: usize = daily.date.iter().position(|time: &String| *time == day)?;
+            let mut line: String = format!("- {}", day_name(date.weekday(), language_code));
+            if let Some(code) = daily.weather_code.get(i).copied().flatten() {
+                line.push_str(&format!(": {}", wmo::describe(code, language_code)));
+            }
+            if let (Some(min), Some(max)) =
+                (at(&daily.temperature_min, i), at(&daily.temperature_max, i))
+            {
+                line.push_str(&format!(", {:.0}°C to {:.0}°C", min, max));
+            }
+            if let Some(total) = at(&daily.precipitation, i).filter(|mm: &f64| *mm > 0.0) {
+                line.push_str(&format!(", {:.1} mm of precipitation", total));
+            }
+            Some(line)
+        })
+        .collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    std::iter::once(format!("Outlook, {}:", heading.to_lowercase())).chain(lines).collect()
+}
+
+fn day_name(weekday: Weekday, language_code: &str) -> &'static str {
+    const FRENCH: [&str; 7] = [
+        "lundi",
+        "mardi",
+        "mercredi",
+        "jeudi",
+        "vendredi",
+        "samedi",
+        "dimanche",
+    ];
+    const ENGLISH: [&str; 7] = [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ];
+    let i: usize = weekday.num_days_from_monday() as usize;
+    match language_code {
+        "fr" => FRENCH[i],
+        _ => ENGLISH[i],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ config::Location, fetch::weather::{ file::WeatherFile, WeatherProvider } };
+
+    // Annecy on Monday 2026-10-19 at 09:00: light rain in the morning, overcast afternoon,
+    // fog in the evening; the hours before 09:00 and after midnight must not count
+    async fn fixture() -> Forecast {
+        let path: &str = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/weather-{city}.json"
+        );
+        let location: Location = Location {
+            city: "Annecy".into(),
+            name: None,
+            latitude: None,
+            longitude: None,
+            country_code: None,
+            admin1: None,
+        };
+        WeatherFile::new(Some(path.into())).forecast(&location).await.unwrap()
+    }
+
+    fn part(period: Period, weathercode: u8, rain_chance: Option<f64>) -> DayPart {
+        DayPart { period, weathercode, min: 10.0, max: 15.0, rain_chance }
+    }
+
+    #[tokio::test]
+    async fn splits_the_rest_of_the_day_into_periods() {
+        let parts: Vec<DayPart> = day_parts(&fixture().await);
+        let periods: Vec<Period> = parts
+            .iter()
+            .map(|p: &DayPart| p.period)
+            .collect();
+        assert_eq!(periods, Period::ALL);
+
+        let (morning, afternoon, evening) = (&parts[0], &parts[1], &parts[2]);
+        assert_eq!((morning.min, morning.max, morning.rain_chance), (10.0, 12.0, Some(80.0)));
+        assert_eq!((afternoon.min, afternoon.max), (13.0, 18.0));
+        assert_eq!((evening.min, evening.max, evening.rain_chance), (10.0, 15.0, Some(10.0)));
+    }
+
+    #[tokio::test]
+    async fn skips_the_hours_already_past() {
+        let mut weather: Forecast = fixture().await;
+        // The -5°C before 09:00 stay out of the morning
+        assert_eq!(day_parts(&weather)[0].min, 10.0);
+
+        weather.current.time = "2026-10-19T16:00".into();
+        let parts: Vec<DayPart> = day_parts(&weather);
+        assert_eq!(parts[0].period, Period::Afternoon);
+        assert_eq!((parts[0].min, parts[0].max), (17.0, 18.0));
+
+        weather.current.time = "2026-10-20T03:00".into();
+        assert!(day_parts(&weather).is_empty());
+    }
+
+    #[tokio::test]
+    async fn dominant_condition_lasts_the_most_hours() {
+        let parts: Vec<DayPart> = day_parts(&fixture().await);
+        // Two hours of light rain against one overcast
+        assert_eq!(parts[0].weathercode, 61);
+        // Three overcast, three partly cloudy: the tie goes to the higher code
+        assert_eq!(parts[1].weathercode, 3);
+        // Codes 44 and 45 are both fog and count together against clear and mainly clear
+        assert_eq!(parts[2].condition("en"), "fog");
+    }
+
+    #[tokio::test]
+    async fn narrative_lists_periods_then_changes() {
+        let lines: Vec<String> = narrative(&fixture().await, "en");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Morning"), "{}", lines[0]);
+        assert!(lines[0].ends_with("light rain, 10°C to 12°C, 80% chance of rain"), "{}", lines[0]);
+        assert_eq!(lines[3], "Changes: clearing up after noon; turning to fog in the evening");
+    }
+
+    #[test]
+    fn describes_changes_between_periods() {
+        let wet: DayPart = part(Period::Morning, 61, Some(80.0));
+        let likely_rain: DayPart = part(Period::Afternoon, 3, Some(60.0));
+        let dry: DayPart = part(Period::Afternoon, 3, Some(10.0));
+
+        assert_eq!(change(&wet, &dry, "en").as_deref(), Some("clearing up after noon"));
+        // Rain only likely, not in the codes: named generically
+        assert_eq!(
+            change(&part(Period::Morning, 2, None), &likely_rain, "en").as_deref(),
+            Some("rain arriving after noon")
+        );
+        assert_eq!(
+            change(&dry, &part(Period::Evening, 95, None), "en").as_deref(),
+            Some("thunderstorm arriving in the evening")
+        );
+        assert_eq!(
+            change(&dry, &part(Period::Evening, 0, None), "fr").as_deref(),
+            Some("turning to ciel dégagé in the evening")
+        );
+        assert_eq!(change(&wet, &part(Period::Afternoon, 63, None), "en"), None);
+        assert_eq!(change(&dry, &part(Period::Evening, 3, Some(20.0)), "en"), None);
+    }
+
+    #[tokio::test]
+    async fn weekend_outlook_keeps_saturday_and_sunday() {
+        let weather: Forecast = fixture().await;
+        let rules: HashMap<Weekday, Outlook> = HashMap::from([(Weekday::Mon, Outlook::Weekend)]);
+        assert_eq!(
+            outlook(&weather, &rules, "en"),
+            [
+                "Outlook, this weekend:",
+                "- Saturday: clear sky, 5°C to 15°C",
+                "- Sunday: light rain, 7°C to 12°C, 3.5 mm of precipitation",
+            ]
+        );
+        assert_eq!(outlook(&weather, &rules, "fr")[1], "- samedi: ciel dégagé, 5°C to 15°C");
+    }
+
+    #[tokio::test]
+    async fn outlook_follows_the_rule_of_the_day() {
+        let weather: Forecast = fixture().await;
+        let tomorrow: HashMap<Weekday, Outlook> = HashMap::from([
+            (Weekday::Mon, Outlook::Tomorrow),
+        ]);
+        assert_eq!(
+            outlook(&weather, &tomorrow, "en"),
+            ["Outlook, tomorrow:", "- Tuesday: overcast, 6°C to 14°C"]
+        );
+        // Tuesday to the next Monday
+        let week: HashMap<Weekday, Outlook> = HashMap::from([(Weekday::Mon, Outlook::Week)]);
+        assert_eq!(outlook(&weather, &week, "en").len(), 1 + 7);
+
+        let other_day: HashMap<Weekday, Outlook> = HashMap::from([(Weekday::Fri, Outlook::Week)]);
+        assert!(outlook(&weather, &other_day, "en").is_empty());
+        let none: HashMap<Weekday, Outlook> = HashMap::from([(Weekday::Mon, Outlook::None)]);
+        assert!(outlook(&weather, &none, "en").is_empty());
+    }
+}
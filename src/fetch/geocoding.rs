@@ -0,0 +1,177 @@
+//! Resolves the configured location to coordinates, once: answers are kept on disk.
+
+use std::{ collections::HashMap, path::{ Path, PathBuf }, sync::{ Mutex, MutexGuard } };
+use reqwest::Client;
+use crate::{
+    config::Location,
+    console,
+    error::{ Error, Result },
+    types::{ GeocodingResponse, GeocodingResult },
+};
+
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+// Enough to find the right one among namesakes when `admin1` is set
+const CANDIDATES: &str = "10";
+
+/// Geocoding answers kept in a JSON file between runs, safe to share between concurrent lookups.
+pub struct GeocodingCache {
+    path: PathBuf,
+    // Serialises the read-merge-write of the file
+    lock: Mutex<()>,
+}
+
+impl GeocodingCache {
+    pub fn new(path: PathBuf) -> Self {
+        GeocodingCache { path, lock: Mutex::new(()) }
+    }
+
+    fn get(&self, key: &str) -> Option<GeocodingResult> {
+        let _guard: MutexGuard<'_, ()> = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        load_cache(&self.path)?.remove(key)
+    }
+
+    // Merged into what is on disk now, so entries added meanwhile by other lookups are kept
+    fn insert(&self, key: String, result: GeocodingResult) -> Result<()> {
+        let _guard: MutexGuard<'_, ()> = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut cache: HashMap<String, GeocodingResult> = load_cache(&self.path).unwrap_or_default();
+        cache.insert(key, result);
+        save_cache(&self.path, &cache)
+    }
+}
+
+/// Coordinates of `location`: the configured ones, a cached answer, or the geocoder's best
+/// match in `country_code` and `admin1` when they are set.
+pub async fn resolve(
+    client: &Client,
+    location: &Location,
+    language_code: &str,
+    cache: Option<&GeocodingCache>
+) -> Result<GeocodingResult> {
+    match (location.latitude, location.longitude) {
+        (Some(latitude), Some(longitude)) => {
+            return Ok(GeocodingResult {
+                latitude,
+                longitude,
+                name: location.city.clone(),
+                admin1: location.admin1.clone(),
+                country_code: location.country_code.clone(),
+            });
+        }
+        (None, None) => {}
+        _ => {
+            return Err(Error::Config("`latitude` and `longitude` must be set together".into()));
+        }
+    }
+
+    let key: String = cache_key(location, language_code);
+    if let Some(cached) = cache.and_then(|cache: &GeocodingCache| cache.get(&key)) {
+        return Ok(cached);
+    }
+
+    let mut query: Vec<(&str, String)> = vec![
+        ("name", location.city.clone()),
+        ("count", CANDIDATES.into()),
+        ("language", language_code.into()),
+        ("format", "json".into())
+    ];
+    if let Some(country_code) = &location.country_code {
+        query.push(("countryCode", country_code.to_uppercase()));
+    }
+    // `query` URL-encodes, so "Saint-Étienne" or "New York" arrive intact
+    let results: Vec<GeocodingResult> = client
+        .get(GEOCODING_URL)
+        .query(&query)
+        .send()
+        .await
+        .and_then(|r: reqwest::Response| r.error_for_status())
+        .map_err(|e| Error::Weather(format!("geocoding failed: {}", e)))?
+        .json::<GeocodingResponse>()
+        .await
+        .map_err(|e| Error::Weather(format!("invalid geocoding response: {}", e)))?
+        .results;
+
+    let candidates: Vec<&GeocodingResult> = results
+        .iter()
+        .filter(|result: &&GeocodingResult| match &location.admin1 {
+            Some(admin1) =>
+                result.admin1
+                    .as_deref()
+                    .is_some_and(|a: &str| a.to_lowercase() == admin1.to_lowercase()),
+            None => true,
+        })
+        .collect();
+    let Some(chosen) = candidates.first().copied() else {
+        return Err(Error::Geocoding(describe(location)));
+    };
+
+    if location.country_code.is_none() && location.admin1.is_none() {
+        let namesakes: Vec<String> = candidates[1..]
+            .iter()
+            .filter(|other: &&&GeocodingResult| other.name == chosen.name)
+            .map(|other: &&GeocodingResult| place_name(other))
+            .collect();
+        if !namesakes.is_empty() {
+            console::warn(
+                format!(
+                    "'{}' is ambiguous, using {} rather than {}; \
+                    set `country_code` or `admin1` to pick another",
+                    location.city,
+                    place_name(chosen),
+                    namesakes.join(", ")
+                )
+            );
+        }
+    }
+
+    let chosen: GeocodingResult = chosen.clone();
+    if let Some(cache) = cache {
+        if let Err(e) = cache.insert(key, chosen.clone()) {
+            console::warn(format!("Failed to cache the coordinates of {}: {}", location.city, e));
+        }
+    }
+    Ok(chosen)
+}
+
+// A change to any part of the query means a new lookup
+fn cache_key(location: &Location, language_code: &str) -> String {
+    [
+        location.city.as_str(),
+        location.country_code.as_deref().unwrap_or(""),
+        location.admin1.as_deref().unwrap_or(""),
+        language_code,
+    ]
+        .map(str::to_lowercase)
+        .join("|")
+}
+
+fn describe(location: &Location) -> String {
+    [Some(location.city.as_str()), location.admin1.as_deref(), location.country_code.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+fn place_name(result: &GeocodingResult) -> String {
+    [Some(result.name.as_str()), result.admin1.as_deref(), result.country_code.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+fn load_cache(path: &Path) -> Option<HashMap<String, GeocodingResult>> {
+    let content: String = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+// Through a temporary file, so a reader never sees half of it
+fn save_cache(path: &Path, cache: &HashMap<String, GeocodingResult>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temporary: PathBuf = path.with_extension("json.tmp");
+    std::fs::write(&temporary, serde_json::to_string_pretty(cache)?)?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
@@ -1,3 +1,6 @@
+pub mod forecast;
+pub mod geocoding;
 pub mod news;
 pub mod weather;
 pub mod types;
+pub mod wmo;
@@ -1,6 +1,6 @@
 use rss::Channel;
 use scraper::{ Html, Selector };
-use crate::types::Article;
+use crate::{ console, error::{ Error, Result }, types::Article };
 use futures::stream::{ self, StreamExt };
 use std::sync::Arc;
 use tokio::sync::{ Mutex, Semaphore };
@@ -8,60 +8,74 @@ use chrono::{ DateTime, Utc, Duration };
 use std::collections::HashSet;
 use std::time::Duration as StdDuration;
 
-pub async fn fetch_news(sources: &[&str]) -> Result<Vec<Article>, Box<dyn std::error::Error>> {
-    let client: Arc<reqwest::Client> = Arc::new(
-        reqwest::Client
-            ::builder()
-            .user_agent("Mozilla/5.0")
-            .timeout(StdDuration::from_secs(30))
-            .pool_idle_timeout(StdDuration::from_secs(15))
-            .pool_max_idle_per_host(10)
-            .build()?
-    );
-
+/// Articles published in the last 24 hours across all RSS `sources`, deduplicated by title.
+/// Sources that fail are reported and skipped; when every one fails, the first error is returned.
+pub async fn fetch_news(sources: &[&str], client: &reqwest::Client) -> Result<Vec<Article>> {
     let shared: Arc<(Semaphore, Mutex<HashSet<String>>)> = Arc::new((
         Semaphore::new(20),
         Mutex::new(HashSet::new()),
     ));
 
-    println!("Starting to fetch {} sources", sources.len());
+    console::status(format!("Starting to fetch {} sources", sources.len()));
 
-    let results: Vec<Article> = stream
-        ::iter(sources.iter().enumerate())
-        .map(|(_, &source)| {
-            let client: Arc<reqwest::Client> = client.clone();
+    // Futures built up front: a stream over the closure is not `Send` (rustc #102211), and the
+    // server spawns whole runs on the runtime
+    let fetches: Vec<_> = sources
+        .iter()
+        .map(|&source| {
+            let client: reqwest::Client = client.clone();
             let shared: Arc<(Semaphore, Mutex<HashSet<String>>)> = shared.clone();
 
             async move {
-                match fetch_source_with_timeout(source, &client, &shared).await {
-                    Ok(articles) => Ok::<_, Box<dyn std::error::Error>>(articles),
-                    Err(e) => {
-                        eprintln!("Error fetching {}: {}", source, e);
-                        Ok::<_, Box<dyn std::error::Error>>(Vec::new())
-                    }
+                let result: Result<Vec<Article>> = fetch_source_with_timeout(
+                    source,
+                    &client,
+                    &shared
+                ).await;
+                if let Err(e) = &result {
+                    console::warn(format!("Error fetching {}: {}", source, e));
                 }
+                result
             }
         })
-        .buffered(10)
-        .collect::<Vec<_>>().await
-        .into_iter()
-        .filter_map(Result::ok)
-        .flatten()
         .collect();
-
-    Ok(results)
+    let outcomes: Vec<Result<Vec<Article>>> = stream
+        ::iter(fetches)
+        .buffered(10)
+        .collect::<Vec<_>>().await;
+
+    let mut results: Vec<Article> = Vec::new();
+    let mut first_error: Option<Error> = None;
+    let mut succeeded: usize = 0;
+    for outcome in outcomes {
+        match outcome {
+            Ok(articles) => {
+                succeeded += 1;
+                results.extend(articles);
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    // A feed error only fails the run when no source could be read at all
+    match first_error {
+        Some(e) if succeeded == 0 => Err(e),
+        _ => Ok(results),
+    }
 }
 
 async fn fetch_source_with_timeout(
     source: &str,
     client: &reqwest::Client,
     shared: &Arc<(Semaphore, Mutex<HashSet<String>>)>
-) -> Result<Vec<Article>, Box<dyn std::error::Error>> {
-    let _permit: tokio::sync::SemaphorePermit<'_> = shared.0.acquire().await?;
-    let timeout: Vec<Article> = tokio::time::timeout(
-        StdDuration::from_secs(30),
-        fetch_source(source, client)
-    ).await??;
+) -> Result<Vec<Article>> {
+    let _permit: tokio::sync::SemaphorePermit<'_> = shared.0
+        .acquire().await
+        .map_err(|e| Error::FeedHttp { url: source.into(), message: e.to_string() })?;
+    let timeout: Vec<Article> = tokio::time
+        ::timeout(StdDuration::from_secs(30), fetch_source(source, client)).await
+        .map_err(|_| Error::FeedHttp { url: source.into(), message: "timed out".into() })??;
     let mut titles: tokio::sync::MutexGuard<'_, HashSet<String>> = shared.1.lock().await;
     let mut duplicates: std::collections::HashMap<&String, i32> = std::collections::HashMap::new();
 
@@ -73,7 +87,7 @@ async fn fetch_source_with_timeout(
 
     for (title, count) in duplicates {
         if count >= 2 {
-            println!("🔄 \"{}\" appears {} times", title, count);
+            console::status(format!("🔄 \"{}\" appears {} times", title, count));
             // found_duplicates = true;
         }
     }
@@ -91,10 +105,20 @@ async fn fetch_source_with_timeout(
 async fn fetch_source(
     source: &str,
     client: &reqwest::Client
-) -> Result<Vec<Article>, Box<dyn std::error::Error>> {
-    let channel: Channel = Channel::read_from(
-        client.get(source).header("Accept-Charset", "UTF-8").send().await?.text().await?.as_bytes()
-    )?;
+) -> Result<Vec<Article>> {
+    let http_error = |e: reqwest::Error| Error::FeedHttp { url: source.into(), message: e.to_string() };
+    let body: String = client
+        .get(source)
+        .header("Accept-Charset", "UTF-8")
+        .send().await
+        .and_then(|r: reqwest::Response| r.error_for_status())
+        .map_err(http_error)?
+        .text().await
+        .map_err(http_error)?;
+    let channel: Channel = Channel::read_from(body.as_bytes()).map_err(|e| Error::FeedParse {
+        url: source.into(),
+        message: e.to_string(),
+    })?;
 
     let now: DateTime<Utc> = Utc::now();
 
@@ -116,6 +140,7 @@ async fn fetch_source(
                             .unwrap_or("No description available")
                             .into(),
                         content: String::new(),
+                        update_on: None,
                     })
                 } else {
                     None
@@ -125,17 +150,23 @@ async fn fetch_source(
     )
 }
 
+/// Download the article page and keep the first 1024 characters of its paragraphs.
 pub async fn fetch_article(
     title: &str,
     link: &str,
+    date: &str,
     description: &str,
     client: &reqwest::Client
-) -> Result<Article, Box<dyn std::error::Error>> {
+) -> Result<Article> {
+    let extraction_error = |e: reqwest::Error| Error::Extraction { url: link.into(), message: e.to_string() };
     let html_content: String = client
         .get(link)
         .header("Accept-Charset", "UTF-8")
-        .send().await?
-        .text().await?;
+        .send().await
+        .and_then(|r: reqwest::Response| r.error_for_status())
+        .map_err(extraction_error)?
+        .text().await
+        .map_err(extraction_error)?;
 
     let content: String = Html::parse_document(&html_content)
         .select(&Selector::parse("article p").unwrap())
@@ -145,12 +176,20 @@ pub async fn fetch_article(
         .chars()
         .take(1024)
         .collect::<String>();
+    // Paywalls and script-rendered pages have no paragraphs, only the headline is left
+    if content.trim().is_empty() {
+        return Err(Error::Extraction {
+            url: link.into(),
+            message: "no article text found".into(),
+        });
+    }
 
     Ok(Article {
         title: title.into(),
         content,
         source: link.into(),
-        date: String::new(),
+        date: date.into(),
         description: description.into(),
+        update_on: None,
     })
 }
@@ -1,53 +1,157 @@
 //News feed types
 
-use serde::Deserialize;
-
-#[derive(Debug)]
-pub struct RssItem {
-    pub title: String,
-    pub description: String,
-    pub link: String,
-    pub date: String,
-}
+use serde::{ Deserialize, Serialize };
+use crate::history::PreviousCoverage;
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Article {
     pub title: String,
     pub content: String,
     pub source: String,
     pub date: String,
     pub description: String,
+    // Set when an earlier version of the story already aired
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_on: Option<PreviousCoverage>,
 }
 
 // Weather types
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GeocodingResponse {
+    // Missing when nothing matches
+    #[serde(default)]
     pub results: Vec<GeocodingResult>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GeocodingResult {
     pub latitude: f64,
     pub longitude: f64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub admin1: Option<String>,
+    #[serde(default)]
+    pub country_code: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct CurrentWeather {
+// Provider-neutral forecast, what every `WeatherProvider` delivers. Times are wall-clock times
+// in `timezone`, formatted `YYYY-MM-DDTHH:MM` (hours) or `YYYY-MM-DD` (days). The aliases read
+// reports saved before the model was split from the Open-Meteo answer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CurrentConditions {
     pub time: String,
+    // °C
     pub temperature: f64,
-    pub weathercode: u8,
+    // WMO 4677 code
+    #[serde(alias = "weathercode")]
+    pub weather_code: u8,
+    // km/h
+    #[serde(default, alias = "windspeed")]
+    pub wind_speed: f64,
+    // Degrees, where the wind comes from
+    #[serde(default, alias = "winddirection")]
+    pub wind_direction: f64,
+}
 
+// One entry per hour, aligned with `time`; values can be missing at the end of the range
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HourlyForecast {
+    #[serde(default)]
+    pub time: Vec<String>,
+    // °C
+    #[serde(alias = "temperature_2m")]
+    pub temperature: Vec<f64>,
+    #[serde(default)]
+    pub feels_like: Vec<Option<f64>>,
+    // WMO 4677 code
+    #[serde(default)]
+    pub weather_code: Vec<Option<u8>>,
+    // %
+    #[serde(default)]
+    pub precipitation_chance: Vec<Option<f64>>,
+    // mm
+    #[serde(default)]
+    pub precipitation: Vec<Option<f64>>,
+    // %
+    #[serde(default)]
+    pub humidity: Vec<Option<f64>>,
+    // km/h
+    #[serde(default)]
+    pub wind_speed: Vec<Option<f64>>,
+    #[serde(default)]
+    pub wind_gusts: Vec<Option<f64>>,
+    #[serde(default)]
+    pub uv_index: Vec<Option<f64>>,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct Hourly {
+// One entry per day, aligned with `date`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DailyForecast {
+    #[serde(default)]
+    pub date: Vec<String>,
+    // Local times
+    #[serde(default)]
+    pub sunrise: Vec<String>,
+    #[serde(default)]
+    pub sunset: Vec<String>,
+    // WMO 4677 code
+    #[serde(default)]
+    pub weather_code: Vec<Option<u8>>,
+    // °C
+    #[serde(default)]
+    pub temperature_min: Vec<Option<f64>>,
+    #[serde(default)]
+    pub temperature_max: Vec<Option<f64>>,
+    // mm
+    #[serde(default)]
+    pub precipitation: Vec<Option<f64>>,
+    // %
+    #[serde(default)]
+    pub precipitation_chance: Vec<Option<f64>>,
+    // km/h
+    #[serde(default)]
+    pub wind_gusts_max: Vec<Option<f64>>,
+    #[serde(default)]
+    pub uv_index_max: Vec<Option<f64>>,
+}
 
-    pub temperature_2m: Vec<f64>,
+// Hourly air quality and pollen, aligned with `time`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AirQuality {
+    #[serde(default)]
+    pub time: Vec<String>,
+    #[serde(default)]
+    pub european_aqi: Vec<Option<f64>>,
+    // µg/m³
+    #[serde(default)]
+    pub pm2_5: Vec<Option<f64>>,
+    #[serde(default)]
+    pub pm10: Vec<Option<f64>>,
+    #[serde(default)]
+    pub ozone: Vec<Option<f64>>,
+    // grains/m³, only available in Europe
+    #[serde(default)]
+    pub birch_pollen: Vec<Option<f64>>,
+    #[serde(default)]
+    pub grass_pollen: Vec<Option<f64>>,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct WeatherResponse {
-    pub current_weather: CurrentWeather,
-    pub hourly: Hourly,
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Forecast {
+    #[serde(alias = "current_weather")]
+    pub current: CurrentConditions,
+    pub hourly: HourlyForecast,
+    #[serde(default)]
+    pub daily: DailyForecast,
+    // IANA name; `hourly` and `daily` times are wall-clock times there
+    #[serde(default)]
+    pub timezone: String,
+    #[serde(default)]
+    pub utc_offset_seconds: i32,
+    // Only fetched when the config has `weather.air_quality`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub air_quality: Option<AirQuality>,
     #[serde(default)]
     pub city: String,
 }
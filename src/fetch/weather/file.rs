@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+use async_trait::async_trait;
+use crate::{
+    config::Location,
+    error::{ Error, Result },
+    types::Forecast,
+};
+use super::{ open_meteo, WeatherProvider };
+
+/// Placeholder in `weather.file` replaced by the location's city, one file per location.
+pub const CITY_PLACEHOLDER: &str = "{city}";
+
+/// A forecast saved as JSON, for tests and offline demos: the `weather` of a script.json, or
+/// an Open-Meteo answer.
+pub struct WeatherFile {
+    path: Option<String>,
+}
+
+impl WeatherFile {
+    /// `path` may hold [`CITY_PLACEHOLDER`] to read a different file per location.
+    pub fn new(path: Option<String>) -> Self {
+        WeatherFile { path }
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for WeatherFile {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn forecast(&self, location: &Location) -> Result<Forecast> {
+        let path: PathBuf = self.path
+            .as_ref()
+            .map(|path: &String| PathBuf::from(path.replace(CITY_PLACEHOLDER, &location.city)))
+            .ok_or_else(|| Error::Config("the file weather provider needs `weather.file`".into()))?;
+        let content: String = tokio::fs
+            ::read_to_string(&path).await
+            .map_err(|e| Error::Weather(format!("{}: {}", path.display(), e)))?;
+        // Open-Meteo first: the model's aliases for old reports would read it, dropping fields
+        let mut forecast: Forecast = open_meteo
+            ::parse_forecast(&content)
+            .or_else(|_| serde_json::from_str(&content))
+            .map_err(|e| Error::Weather(format!("invalid forecast in {}: {}", path.display(), e)))?;
+        forecast.city = location.display_name().to_string();
+        Ok(forecast)
+    }
+}
@@ -0,0 +1,381 @@
+pub mod file;
+pub mod open_meteo;
+
+use std::{ path::{ Path, PathBuf }, sync::Arc };
+use async_trait::async_trait;
+use chrono::{ DateTime, NaiveDateTime, NaiveTime, TimeZone, Timelike };
+use chrono_tz::Tz;
+use reqwest::Client;
+use crate::config::{ AirQualityConfig, Config, Location, WeatherBackend };
+use crate::error::{ Error, Result };
+use crate::types::{ CurrentConditions, Forecast };
+use super::wmo;
+
+// Above these, the forecast gets called out so listeners can plan for it
+pub(crate) const LIKELY_PRECIPITATION: f64 = 50.0;
+const STRONG_GUSTS: f64 = 60.0;
+const HIGH_UV: f64 = 6.0;
+
+/// A source of forecasts, all delivered in the provider-neutral [`Forecast`] model.
+#[async_trait]
+pub trait WeatherProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn forecast(&self, location: &Location) -> Result<Forecast>;
+}
+
+/// The provider `weather.provider` selects; fails when its settings can't serve every location.
+pub fn build_provider(config: &Config, client: &Client) -> Result<Arc<dyn WeatherProvider>> {
+    match config.weather.provider {
+        WeatherBackend::OpenMeteo => {
+            let geocoding_cache: PathBuf = Path::new(&config.output_dir).join("geocoding.json");
+            Ok(
+                Arc::new(
+                    open_meteo::OpenMeteo::new(client.clone(), config.language_code())
+                        .with_geocoding_cache(geocoding_cache)
+                        .with_air_quality(config.weather.air_quality.is_some())
+                )
+            )
+        }
+        WeatherBackend::File => {
+            let per_location: bool = config.weather.file
+                .as_deref()
+                .is_some_and(|path: &str| path.contains(file::CITY_PLACEHOLDER));
+            // One file would give every location the same weather
+            if !config.locations.is_empty() && !per_location {
+                return Err(
+                    Error::Config(
+                        format!(
+                            "with `locations`, `weather.file` needs a {} placeholder",
+                            file::CITY_PLACEHOLDER
+                        )
+                    )
+                );
+            }
+            Ok(Arc::new(file::WeatherFile::new(config.weather.file.clone())))
+        }
+    }
+}
+
+impl CurrentConditions {
+    /// Conditions in `language_code`, see [`wmo::describe`].
+    pub fn get_weather_description(&self, language_code: &str) -> &'static str {
+        wmo::describe(self.weather_code, language_code)
+    }
+}
+
+impl Forecast {
+    /// City, current conditions in `language_code` and today's range, on one line.
+    pub fn get_day_forecast(&self, language_code: &str) -> String {
+        let description: &str = self.current.get_weather_description(language_code);
+        let Some((min_temp, max_temp)) = self.today_range() else {
+            return format!("{}: {}", self.city, description);
+        };
+
+        format!("{}: {}, {:.1}°C to {:.1}°C",
+            self.city,
+            description,
+            min_temp,
+            max_temp
+        )
+    }
+
+    /// Lowest and highest temperature of the local day.
+    pub fn today_range(&self) -> Option<(f64, f64)> {
+        let temps: Vec<f64> = self.today_hours()
+            .into_iter()
+            .filter_map(|i: usize| self.hourly.temperature.get(i).copied())
+            .collect();
+        if temps.is_empty() {
+            return None;
+        }
+        Some(
+            temps.iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &temp|
+                    (min.min(temp), max.max(temp))
+                )
+        )
+    }
+
+    /// Time zone of the location, UTC for reports fetched before it was requested.
+    pub fn zone(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    /// An Open-Meteo timestamp, which is in the location's time zone.
+    pub fn zoned_time(&self, time: &str) -> Option<DateTime<Tz>> {
+        let local: NaiveDateTime = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").ok()?;
+        self.zone().from_local_datetime(&local).earliest()
+    }
+
+    /// Wall-clock time at the location of an Open-Meteo timestamp.
+    pub fn local_time(&self, time: &str) -> Option<NaiveDateTime> {
+        self.zoned_time(time).map(|t: DateTime<Tz>| t.naive_local())
+    }
+
+    /// Wall-clock time at the location of the current conditions.
+    pub fn local_now(&self) -> Option<NaiveDateTime> {
+        self.local_time(&self.current.time)
+    }
+
+    /// Indices in `hourly` of the local calendar day the current conditions fall in.
+    pub fn today_hours(&self) -> Vec<usize> {
+        let Some(today) = self.local_now().map(|now: NaiveDateTime| now.date()) else {
+            return Vec::new();
+        };
+        self.hourly.time
+            .iter()
+            .enumerate()
+            .filter(|(_, time)| {
+                self.local_time(time).map(|t: NaiveDateTime| t.date()) == Some(today)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // An Open-Meteo timestamp as it is said in `language_code`
+    fn clock(&self, time: &str, language_code: &str) -> String {
+        match self.local_time(time) {
+            Some(local) => spoken_time(local.time(), language_code),
+            None => time.split_once('T').map_or(time, |(_, clock)| clock).to_string(),
+        }
+    }
+
+    /// Current conditions, the rest of today and what to watch out for, one fact per line,
+    /// with times written the way they are said in `language_code`.
+    pub fn get_detailed_forecast(&self, language_code: &str) -> String {
+        let current: &CurrentConditions = &self.current;
+        let now: usize = self.current_hour();
+        let today: Vec<usize> = self.today_hours()
+            .into_iter()
+            .filter(|i: &usize| *i >= now)
+            .collect();
+        let at = |series: &[Option<f64>], i: usize| series.get(i).copied().flatten();
+
+        let mut now_line: String = format!(
+            "Current weather in {} at {}: {}, {:.1}°C",
+            self.city,
+            self.clock(&current.time, language_code),
+            current.get_weather_description(language_code),
+            current.temperature
+        );
+        if let Some(apparent) = at(&self.hourly.feels_like, now) {
+            now_line.push_str(&format!(" (feels like {:.1}°C)", apparent));
+        }
+        if let Some(humidity) = at(&self.hourly.humidity, now) {
+            now_line.push_str(&format!(", humidity {:.0}%", humidity));
+        }
+        now_line.push_str(&format!(", wind {:.0} km/h", current.wind_speed));
+        if let Some(gusts) = at(&self.hourly.wind_gusts, now) {
+            now_line.push_str(&format!(" gusting to {:.0} km/h", gusts));
+        }
+        let mut lines: Vec<String> = vec![now_line];
+
+        let daily = &self.daily;
+        lines.extend(super::forecast::narrative(self, language_code));
+        if let (Some(sunrise), Some(sunset)) = (daily.sunrise.first(), daily.sunset.first()) {
+            let sunrise: String = self.clock(sunrise, language_code);
+            let sunset: String = self.clock(sunset, language_code);
+            lines.push(format!("Sunrise at {}, sunset at {}", sunrise, sunset));
+        }
+        if let Some(Some(probability)) = daily.precipitation_chance.first() {
+            let total: f64 = daily.precipitation.first().copied().flatten().unwrap_or(0.0);
+            lines.push(
+                format!("Precipitation: up to {:.0}% chance, {:.1} mm expected", probability, total)
+            );
+        }
+
+        // Consecutive rainy hours read as one span: first hour, last hour, highest chance
+        let mut spells: Vec<(usize, usize, f64)> = Vec::new();
+        for i in &today {
+            let Some(p) = at(&self.hourly.precipitation_chance, *i) else {
+                continue;
+            };
+            if p < LIKELY_PRECIPITATION {
+                continue;
+            }
+            match spells.last_mut() {
+                Some((_, last, top)) if *last + 1 == *i => {
+                    *last = *i;
+                    *top = top.max(p);
+                }
+                _ => spells.push((*i, *i, p)),
+            }
+        }
+        if !spells.is_empty() {
+            let spells: Vec<String> = spells
+                .iter()
+                .map(|(first, last, top)| {
+                    let from: String = self.clock(&self.hourly.time[*first], language_code);
+                    if first == last {
+                        format!("at {} ({:.0}%)", from, top)
+                    } else {
+                        let to: String = self.clock(&self.hourly.time[*last], language_code);
+                        format!("from {} to {} (up to {:.0}%)", from, to, top)
+                    }
+                })
+                .collect();
+            lines.push(format!("Alert: rain likely {}, bring an umbrella", spells.join(", ")));
+        }
+        if let Some((i, gusts)) = peak(&self.hourly.wind_gusts, &today) {
+            if gusts >= STRONG_GUSTS {
+                let hour: String = self.clock(&self.hourly.time[i], language_code);
+                lines.push(format!("Alert: strong gusts up to {:.0} km/h around {}", gusts, hour));
+            }
+        }
+        if let Some((i, uv)) = peak(&self.hourly.uv_index, &today) {
+            let hour: String = self.clock(&self.hourly.time[i], language_code);
+            if uv >= HIGH_UV {
+                lines.push(
+                    format!("Alert: high UV index of {:.0} around {}, use sun protection", uv, hour)
+                );
+            } else {
+                lines.push(format!("Maximum UV index {:.0} around {}", uv, hour));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Air quality and pollen reaching `thresholds` over the rest of today, one line each;
+    /// empty when there is nothing worth mentioning.
+    pub fn get_air_quality_alerts(
+        &self,
+        thresholds: &AirQualityConfig,
+        language_code: &str
+    ) -> Vec<String> {
+        let (Some(air), Some(now)) = (&self.air_quality, self.local_now()) else {
+            return Vec::new();
+        };
+        let hours: Vec<usize> = air.time
+            .iter()
+            .enumerate()
+            .filter(|(_, time)| {
+                self.local_time(time).is_some_and(|t: NaiveDateTime| {
+                    t.date() == now.date() && t.hour() >= now.hour()
+                })
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let check = |name, series, threshold, unit| AirQualityCheck {
+            name,
+            series,
+            threshold,
+            unit,
+            band: None,
+        };
+        let checks: [AirQualityCheck; 6] = [
+            AirQualityCheck {
+                band: Some(european_aqi_level),
+                ..check(
+                    "European air quality index",
+                    &air.european_aqi,
+                    thresholds.european_aqi,
+                    ""
+                )
+            },
+            check("Fine particles (PM2.5)", &air.pm2_5, thresholds.pm2_5, " µg/m³"),
+            check("Particles (PM10)", &air.pm10, thresholds.pm10, " µg/m³"),
+            check("Ozone", &air.ozone, thresholds.ozone, " µg/m³"),
+            check("Birch pollen", &air.birch_pollen, thresholds.birch_pollen, " grains/m³"),
+            check("Grass pollen", &air.grass_pollen, thresholds.grass_pollen, " grains/m³"),
+        ];
+        checks
+            .iter()
+            .filter_map(|check: &AirQualityCheck| {
+                let (i, value) = peak(check.series, &hours).filter(|(_, value)| {
+                    *value >= check.threshold
+                })?;
+                let mut line: String = format!(
+                    "Alert: {} up to {:.0}{} around {}",
+                    check.name,
+                    value,
+                    check.unit,
+                    self.clock(&air.time[i], language_code)
+                );
+                if let Some(band) = check.band {
+                    line.push_str(&format!(" ({})", band(value)));
+                }
+                Some(line)
+            })
+            .collect()
+    }
+
+    // Index in `hourly` of the hour the current conditions fall in
+    fn current_hour(&self) -> usize {
+        // 10:15 belongs to the 10:00 hour
+        let Some(now) = self.local_now() else {
+            return 0;
+        };
+        self.hourly.time
+            .iter()
+            .position(|time: &String| {
+                self.local_time(time).is_some_and(|t: NaiveDateTime| {
+                    t.date() == now.date() && t.hour() == now.hour()
+                })
+            })
+            .unwrap_or(0)
+    }
+}
+
+// One pollutant or pollen compared to its threshold
+struct AirQualityCheck<'a> {
+    name: &'static str,
+    series: &'a [Option<f64>],
+    threshold: f64,
+    unit: &'static str,
+    // Name of the band a value falls in, for indices that have them
+    band: Option<fn(f64) -> &'static str>,
+}
+
+// Bands of the European Air Quality Index
+fn european_aqi_level(value: f64) -> &'static str {
+    match value {
+        v if v < 20.0 => "good",
+        v if v < 40.0 => "fair",
+        v if v < 60.0 => "moderate",
+        v if v < 80.0 => "poor",
+        v if v < 100.0 => "very poor",
+        _ => "extremely poor",
+    }
+}
+
+/// A time of day as a presenter would say it: "7 h 30" in French, "7:30 am" in English.
+pub fn spoken_time(time: NaiveTime, language_code: &str) -> String {
+    let (hour, minute) = (time.hour(), time.minute());
+    match language_code {
+        "fr" =>
+            match (hour, minute) {
+                (0, 0) => "minuit".to_string(),
+                (12, 0) => "midi".to_string(),
+                (_, 0) => format!("{} h", hour),
+                _ => format!("{} h {:02}", hour, minute),
+            }
+        "en" => {
+            let (hour12, suffix) = match hour {
+                0 => (12, "am"),
+                1..=11 => (hour, "am"),
+                12 => (12, "pm"),
+                _ => (hour - 12, "pm"),
+            };
+            match (hour, minute) {
+                (0, 0) => "midnight".to_string(),
+                (12, 0) => "noon".to_string(),
+                (_, 0) => format!("{} {}", hour12, suffix),
+                _ => format!("{}:{:02} {}", hour12, minute, suffix),
+            }
+        }
+        _ => time.format("%H:%M").to_string(),
+    }
+}
+
+// Highest value over `hours`, with its index
+fn peak(series: &[Option<f64>], hours: &[usize]) -> Option<(usize, f64)> {
+    hours
+        .iter()
+        .filter_map(|i: &usize| series.get(*i).copied().flatten().map(|value: f64| (*i, value)))
+        .fold(None, |best: Option<(usize, f64)>, (i, value)| match best {
+            Some((_, top)) if top >= value => best,
+            _ => Some((i, value)),
+        })
+}
@@ -0,0 +1,263 @@
+use std::path::PathBuf;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use crate::{
+    config::Location,
+    console,
+    error::{ Error, Result },
+    fetch::geocoding::{ self, GeocodingCache },
+    types::{
+        AirQuality,
+        CurrentConditions,
+        DailyForecast,
+        Forecast,
+        GeocodingResult,
+        HourlyForecast,
+    },
+};
+use super::WeatherProvider;
+
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+const AIR_QUALITY_URL: &str = "https://air-quality-api.open-meteo.com/v1/air-quality";
+const HOURLY: &str =
+    "temperature_2m,apparent_temperature,weather_code,precipitation_probability,precipitation,\
+    relative_humidity_2m,wind_speed_10m,wind_gusts_10m,uv_index";
+const DAILY: &str =
+    "sunrise,sunset,weather_code,temperature_2m_min,temperature_2m_max,precipitation_sum,\
+    precipitation_probability_max,wind_gusts_10m_max,uv_index_max";
+// Today and a full week after it, for the week-ahead outlook
+const FORECAST_DAYS: &str = "8";
+
+/// open-meteo.com: geocoding, forecast and optionally air quality, no key needed.
+pub struct OpenMeteo {
+    client: Client,
+    language_code: String,
+    geocoding_cache: Option<GeocodingCache>,
+    air_quality: bool,
+}
+
+impl OpenMeteo {
+    /// Place names are looked up in `language_code`.
+    pub fn new(client: Client, language_code: &str) -> Self {
+        OpenMeteo {
+            client,
+            language_code: language_code.to_string(),
+            geocoding_cache: None,
+            air_quality: false,
+        }
+    }
+
+    /// Keep geocoding answers in this JSON file between runs.
+    pub fn with_geocoding_cache(mut self, path: PathBuf) -> Self {
+        self.geocoding_cache = Some(GeocodingCache::new(path));
+        self
+    }
+
+    /// Also fetch air quality and pollen.
+    pub fn with_air_quality(mut self, air_quality: bool) -> Self {
+        self.air_quality = air_quality;
+        self
+    }
+
+    /// Today's hourly air quality and pollen at `coords`.
+    pub async fn fetch_air_quality(&self, coords: &GeocodingResult) -> Result<AirQuality> {
+        let response: AirQualityResponse = self.client
+            .get(AIR_QUALITY_URL)
+            .query(&coordinates(coords))
+            .query(
+                &[
+                    ("hourly", "european_aqi,pm2_5,pm10,ozone,birch_pollen,grass_pollen"),
+                    ("forecast_days", "1"),
+                    ("timezone", "auto"),
+                ]
+            )
+            .send()
+            .await
+            .and_then(|r: reqwest::Response| r.error_for_status())
+            .map_err(|e| Error::Weather(format!("air quality: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| Error::Weather(format!("invalid air quality response: {}", e)))?;
+        Ok(response.hourly)
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for OpenMeteo {
+    fn name(&self) -> &'static str {
+        "Open-Meteo"
+    }
+
+    async fn forecast(&self, location: &Location) -> Result<Forecast> {
+        let coords: GeocodingResult = geocoding::resolve(
+            &self.client,
+            location,
+            &self.language_code,
+            self.geocoding_cache.as_ref()
+        ).await?;
+
+        let response: ForecastResponse = self.client
+            .get(FORECAST_URL)
+            .query(&coordinates(&coords))
+            .query(
+                &[
+                    ("current_weather", "true"),
+                    ("hourly", HOURLY),
+                    ("daily", DAILY),
+                    ("forecast_days", FORECAST_DAYS),
+                    ("timezone", "auto"),
+                ]
+            )
+            .send()
+            .await
+            .and_then(|r: reqwest::Response| r.error_for_status())
+            .map_err(|e| Error::Weather(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| Error::Weather(format!("invalid forecast response: {}", e)))?;
+
+        let mut forecast: Forecast = response.into();
+        forecast.city = location.display_name().to_string();
+        // Nice to have: the forecast airs without it
+        if self.air_quality {
+            match self.fetch_air_quality(&coords).await {
+                Ok(air_quality) => {
+                    forecast.air_quality = Some(air_quality);
+                }
+                Err(e) => console::warn(format!("Skipping air quality: {}", e)),
+            }
+        }
+        Ok(forecast)
+    }
+}
+
+fn coordinates(coords: &GeocodingResult) -> [(&'static str, String); 2] {
+    [("latitude", coords.latitude.to_string()), ("longitude", coords.longitude.to_string())]
+}
+
+/// A forecast saved straight from the Open-Meteo API.
+pub fn parse_forecast(json: &str) -> serde_json::Result<Forecast> {
+    serde_json::from_str::<ForecastResponse>(json).map(Forecast::from)
+}
+
+// The API's own field names, only used to read its answers
+
+#[derive(Deserialize)]
+struct ForecastResponse {
+    current_weather: CurrentWeather,
+    hourly: Hourly,
+    #[serde(default)]
+    daily: Daily,
+    #[serde(default)]
+    timezone: String,
+    #[serde(default)]
+    utc_offset_seconds: i32,
+}
+
+#[derive(Deserialize)]
+struct CurrentWeather {
+    time: String,
+    temperature: f64,
+    weathercode: u8,
+    #[serde(default)]
+    windspeed: f64,
+    #[serde(default)]
+    winddirection: f64,
+}
+
+#[derive(Deserialize)]
+struct Hourly {
+    #[serde(default)]
+    time: Vec<String>,
+    temperature_2m: Vec<f64>,
+    #[serde(default)]
+    apparent_temperature: Vec<Option<f64>>,
+    #[serde(default)]
+    weather_code: Vec<Option<u8>>,
+    #[serde(default)]
+    precipitation_probability: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation: Vec<Option<f64>>,
+    #[serde(default)]
+    relative_humidity_2m: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_speed_10m: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_gusts_10m: Vec<Option<f64>>,
+    #[serde(default)]
+    uv_index: Vec<Option<f64>>,
+}
+
+#[derive(Deserialize, Default)]
+struct Daily {
+    #[serde(default)]
+    time: Vec<String>,
+    #[serde(default)]
+    sunrise: Vec<String>,
+    #[serde(default)]
+    sunset: Vec<String>,
+    #[serde(default)]
+    weather_code: Vec<Option<u8>>,
+    #[serde(default)]
+    temperature_2m_min: Vec<Option<f64>>,
+    #[serde(default)]
+    temperature_2m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_sum: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_probability_max: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_gusts_10m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    uv_index_max: Vec<Option<f64>>,
+}
+
+// Same names as the model
+#[derive(Deserialize)]
+struct AirQualityResponse {
+    hourly: AirQuality,
+}
+
+impl From<ForecastResponse> for Forecast {
+    fn from(response: ForecastResponse) -> Self {
+        let ForecastResponse { current_weather: current, hourly, daily, .. } = response;
+        Forecast {
+            current: CurrentConditions {
+                time: current.time,
+                temperature: current.temperature,
+                weather_code: current.weathercode,
+                wind_speed: current.windspeed,
+                wind_direction: current.winddirection,
+            },
+            hourly: HourlyForecast {
+                time: hourly.time,
+                temperature: hourly.temperature_2m,
+                feels_like: hourly.apparent_temperature,
+                weather_code: hourly.weather_code,
+                precipitation_chance: hourly.precipitation_probability,
+                precipitation: hourly.precipitation,
+                humidity: hourly.relative_humidity_2m,
+                wind_speed: hourly.wind_speed_10m,
+                wind_gusts: hourly.wind_gusts_10m,
+                uv_index: hourly.uv_index,
+            },
+            daily: DailyForecast {
+                date: daily.time,
+                sunrise: daily.sunrise,
+                sunset: daily.sunset,
+                weather_code: daily.weather_code,
+                temperature_min: daily.temperature_2m_min,
+                temperature_max: daily.temperature_2m_max,
+                precipitation: daily.precipitation_sum,
+                precipitation_chance: daily.precipitation_probability_max,
+                wind_gusts_max: daily.wind_gusts_10m_max,
+                uv_index_max: daily.uv_index_max,
+            },
+            timezone: response.timezone,
+            utc_offset_seconds: response.utc_offset_seconds,
+            air_quality: None,
+            city: String::new(),
+        }
+    }
+}
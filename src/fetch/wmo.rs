@@ -0,0 +1,169 @@
+//! WMO 4677 present-weather codes, as reported by Open-Meteo, in the broadcast languages.
+
+// (English, French) for codes 0 to 99; 0 to 3 follow Open-Meteo, which uses them for cloud cover
+const CODES: [(&str, &str); 100] = [
+    // 00-09: no precipitation, fog, dust or sand
+    ("clear sky", "ciel dégagé"),
+    ("mainly clear", "ciel peu nuageux"),
+    ("partly cloudy", "ciel partiellement nuageux"),
+    ("overcast", "ciel couvert"),
+    ("smoke haze", "visibilité réduite par la fumée"),
+    ("haze", "brume sèche"),
+    ("dust in the air", "poussière en suspension"),
+    ("blowing dust or sand", "poussière ou sable soulevés par le vent"),
+    ("dust whirls", "tourbillons de poussière"),
+    ("duststorm in sight", "tempête de poussière à proximité"),
+    // 10-19: no precipitation
+    ("mist", "brume"),
+    ("patches of shallow fog", "bancs de brouillard bas"),
+    ("shallow fog", "brouillard bas"),
+    ("lightning without thunder", "éclairs sans tonnerre"),
+    ("precipitation aloft", "précipitations n'atteignant pas le sol"),
+    ("distant precipitation", "précipitations au loin"),
+    ("precipitation nearby", "précipitations à proximité"),
+    ("thunder without rain", "orage sans précipitations"),
+    ("squalls", "grains"),
+    ("funnel clouds", "trombes"),
+    // 20-29: precipitation during the past hour, not now
+    ("drizzle earlier", "bruine récente"),
+    ("rain earlier", "pluie récente"),
+    ("snow earlier", "neige récente"),
+    ("sleet earlier", "neige mêlée de pluie récente"),
+    ("freezing rain earlier", "pluie verglaçante récente"),
+    ("rain showers earlier", "averses de pluie récentes"),
+    ("snow showers earlier", "averses de neige récentes"),
+    ("hail showers earlier", "averses de grêle récentes"),
+    ("fog earlier", "brouillard récent"),
+    ("thunderstorm earlier", "orage récent"),
+    // 30-39: duststorm, sandstorm, blowing snow
+    ("duststorm easing", "tempête de poussière en diminution"),
+    ("duststorm", "tempête de poussière"),
+    ("duststorm strengthening", "tempête de poussière en renforcement"),
+    ("severe duststorm easing", "forte tempête de poussière en diminution"),
+    ("severe duststorm", "forte tempête de poussière"),
+    ("severe duststorm strengthening", "forte tempête de poussière en renforcement"),
+    ("drifting snow", "chasse-neige basse"),
+    ("heavy drifting snow", "forte chasse-neige basse"),
+    ("blowing snow", "chasse-neige élevée"),
+    ("heavy blowing snow", "forte chasse-neige élevée"),
+    // 40-49: fog
+    ("fog in the distance", "brouillard au loin"),
+    ("fog patches", "bancs de brouillard"),
+    ("thinning fog", "brouillard en dissipation"),
+    ("thick fog, thinning", "brouillard épais en dissipation"),
+    ("fog", "brouillard"),
+    ("fog", "brouillard"),
+    ("thickening fog", "brouillard s'épaississant"),
+    ("thick fog, thickening", "brouillard épais s'épaississant"),
+    ("freezing fog", "brouillard givrant"),
+    ("thick freezing fog", "brouillard givrant épais"),
+    // 50-59: drizzle
+    ("light intermittent drizzle", "faible bruine intermittente"),
+    ("light drizzle", "faible bruine"),
+    ("moderate intermittent drizzle", "bruine modérée intermittente"),
+    ("moderate drizzle", "bruine modérée"),
+    ("heavy intermittent drizzle", "forte bruine intermittente"),
+    ("heavy drizzle", "forte bruine"),
+    ("light freezing drizzle", "faible bruine verglaçante"),
+    ("heavy freezing drizzle", "forte bruine verglaçante"),
+    ("light drizzle and rain", "faible bruine et pluie"),
+    ("heavy drizzle and rain", "forte bruine et pluie"),
+    // 60-69: rain
+    ("light intermittent rain", "faible pluie intermittente"),
+    ("light rain", "faible pluie"),
+    ("moderate intermittent rain", "pluie modérée intermittente"),
+    ("moderate rain", "pluie modérée"),
+    ("heavy intermittent rain", "forte pluie intermittente"),
+    ("heavy rain", "forte pluie"),
+    ("light freezing rain", "faible pluie verglaçante"),
+    ("heavy freezing rain", "forte pluie verglaçante"),
+    ("light sleet", "faible pluie et neige mêlées"),
+    ("heavy sleet", "forte pluie et neige mêlées"),
+    // 70-79: solid precipitation, not showers
+    ("light intermittent snow", "faible neige intermittente"),
+    ("light snow", "faible neige"),
+    ("moderate intermittent snow", "neige modérée intermittente"),
+    ("moderate snow", "neige modérée"),
+    ("heavy intermittent snow", "forte neige intermittente"),
+    ("heavy snow", "forte neige"),
+    ("diamond dust", "poudrin de glace"),
+    ("snow grains", "neige en grains"),
+    ("isolated snow crystals", "cristaux de neige isolés"),
+    ("ice pellets", "granules de glace"),
+    // 80-99: showers and thunderstorms
+    ("light rain showers", "faibles averses de pluie"),
+    ("moderate rain showers", "averses de pluie modérées"),
+    ("violent rain showers", "violentes averses de pluie"),
+    ("light sleet showers", "faibles averses de pluie et neige"),
+    ("heavy sleet showers", "fortes averses de pluie et neige"),
+    ("light snow showers", "faibles averses de neige"),
+    ("heavy snow showers", "fortes averses de neige"),
+    ("light small hail showers", "faibles averses de grésil"),
+    ("heavy small hail showers", "fortes averses de grésil"),
+    ("light hail showers", "faibles averses de grêle"),
+    ("heavy hail showers", "fortes averses de grêle"),
+    ("light rain after a thunderstorm", "faible pluie après un orage"),
+    ("heavy rain after a thunderstorm", "forte pluie après un orage"),
+    ("light snow or hail after a thunderstorm", "faible neige ou grêle après un orage"),
+    ("heavy snow or hail after a thunderstorm", "forte neige ou grêle après un orage"),
+    ("thunderstorm", "orage"),
+    ("thunderstorm with hail", "orage avec grêle"),
+    ("heavy thunderstorm", "violent orage"),
+    ("thunderstorm with duststorm", "orage avec tempête de poussière"),
+    ("heavy thunderstorm with hail", "violent orage avec grêle"),
+];
+
+/// Description of a WMO 4677 code in `language_code`, English when the language isn't covered.
+pub fn describe(code: u8, language_code: &str) -> &'static str {
+    let Some((english, french)) = CODES.get(code as usize) else {
+        return match language_code {
+            "fr" => "conditions inconnues",
+            _ => "unknown conditions",
+        };
+    };
+    match language_code {
+        "fr" => french,
+        _ => english,
+    }
+}
+
+/// Whether the code reports precipitation falling at the time of observation.
+pub fn is_precipitation(code: u8) -> bool {
+    matches!(code, 50..=99)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_codes_in_french_and_english() {
+        assert_eq!(describe(0, "en"), "clear sky");
+        assert_eq!(describe(0, "fr"), "ciel dégagé");
+        assert_eq!(describe(61, "en"), "light rain");
+        assert_eq!(describe(61, "fr"), "faible pluie");
+        assert_eq!(describe(95, "en"), "thunderstorm");
+        assert_eq!(describe(44, "en"), describe(45, "en"));
+    }
+
+    #[test]
+    fn other_languages_get_english() {
+        assert_eq!(describe(3, "de"), describe(3, "en"));
+        assert_eq!(describe(3, ""), "overcast");
+    }
+
+    #[test]
+    fn unknown_codes_are_said_as_such() {
+        assert_eq!(describe(100, "en"), "unknown conditions");
+        assert_eq!(describe(u8::MAX, "fr"), "conditions inconnues");
+    }
+
+    #[test]
+    fn precipitation_starts_at_drizzle() {
+        assert!(!is_precipitation(3));
+        assert!(!is_precipitation(49));
+        assert!(is_precipitation(50));
+        assert!(is_precipitation(99));
+        assert!(!is_precipitation(100));
+    }
+}
@@ -1,11 +1,13 @@
-use crate::config::FilterConfig;
+use async_trait::async_trait;
+use crate::{ config::FilterConfig, error::Result, types::Article };
+use super::{ FilterStage, Verdict };
 
-//filter who remove all atricle who have banned word on title or description
+/// Returns `false` when the title or description contains a banned word (or its plural/singular).
 pub async fn banned(
     title: &str,
     description: &str,
     filter_config: &FilterConfig
-) -> Result<bool, Box<dyn std::error::Error>> {
+) -> Result<bool> {
     let title_lower: String = title.to_lowercase();
     let description_lower: String = description.to_lowercase();
     let mut is_relevant: bool = true;
@@ -31,3 +33,22 @@ pub async fn banned(
     }
     Ok(is_relevant)
 }
+
+/// Drops articles containing a banned word.
+pub struct BannedFilter {
+    pub filter: FilterConfig,
+}
+
+#[async_trait]
+impl FilterStage for BannedFilter {
+    fn name(&self) -> &str {
+        "banned words"
+    }
+
+    async fn keep(&self, article: &Article, verdict: &mut Verdict) -> Result<bool> {
+        // `banned` returns true when the article is free of banned words
+        let not_banned: bool = banned(&article.title, &article.description, &self.filter).await?;
+        verdict.banned = !not_banned;
+        Ok(not_banned)
+    }
+}
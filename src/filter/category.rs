@@ -1,11 +1,13 @@
-use crate::config::FilterConfig;
+use async_trait::async_trait;
+use crate::{ config::FilterConfig, error::Result, types::Article };
+use super::{ FilterStage, Verdict };
 
-//filter who remove only from ai filter when categories are in title or description
+/// Returns `true` when the title or description mentions one of the categories.
 pub async fn category(
     title: &str,
     description: &str,
     filter_config: &FilterConfig
-) -> Result<bool, Box<dyn std::error::Error>> {
+) -> Result<bool> {
     let title_lower: String = title.to_lowercase();
     let description_lower: String = description.to_lowercase();
     let mut is_relevant: bool = false;
@@ -31,3 +33,20 @@ pub async fn category(
     }
     Ok(is_relevant)
 }
+
+/// Flags keyword category matches, informative only: every article goes on.
+pub struct CategoryFilter {
+    pub filter: FilterConfig,
+}
+
+#[async_trait]
+impl FilterStage for CategoryFilter {
+    fn name(&self) -> &str {
+        "categories"
+    }
+
+    async fn keep(&self, article: &Article, verdict: &mut Verdict) -> Result<bool> {
+        verdict.category_match = category(&article.title, &article.description, &self.filter).await?;
+        Ok(true)
+    }
+}
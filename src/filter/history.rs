@@ -0,0 +1,40 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::{ console, error::Result, history::{ History, Seen }, types::Article };
+use super::{ FilterStage, Verdict };
+
+/// Drops stories that already aired; changed ones go on as updates when `updates` is set.
+pub struct HistoryFilter {
+    pub history: Arc<History>,
+    pub updates: bool,
+}
+
+#[async_trait]
+impl FilterStage for HistoryFilter {
+    fn name(&self) -> &str {
+        "history"
+    }
+
+    async fn keep(&self, article: &Article, verdict: &mut Verdict) -> Result<bool> {
+        // A broken history must not take the news down, the story may air twice at worst
+        let seen: Seen = match self.history.seen(article) {
+            Ok(seen) => seen,
+            Err(e) => {
+                console::warn(format!("History lookup failed for {}: {}", article.source, e));
+                return Ok(true);
+            }
+        };
+        match seen {
+            Seen::New => Ok(true),
+            Seen::Aired => {
+                verdict.already_aired = true;
+                Ok(false)
+            }
+            Seen::Updated(previous) => {
+                verdict.already_aired = true;
+                verdict.update_on = Some(previous);
+                Ok(self.updates)
+            }
+        }
+    }
+}
@@ -1,2 +1,61 @@
 pub mod category;
 pub mod banned;
+pub mod history;
+
+use async_trait::async_trait;
+use serde::{ Deserialize, Serialize };
+use crate::{ error::Result, history::PreviousCoverage, output::Degradation, types::Article };
+
+/// What each filter stage decided about one article.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Verdict {
+    pub title: String,
+    pub source: String,
+    pub banned: bool,
+    pub category_match: bool,
+    /// `None` when the article never reached the AI filter.
+    pub ai_relevant: Option<bool>,
+    /// The AI verdict came from the cache of a previous run.
+    #[serde(default)]
+    pub ai_cached: bool,
+    #[serde(default)]
+    pub already_aired: bool,
+    /// What aired before, when the story changed since.
+    #[serde(default)]
+    pub update_on: Option<PreviousCoverage>,
+}
+
+impl Verdict {
+    pub fn new(article: &Article) -> Self {
+        Verdict {
+            title: article.title.clone(),
+            source: article.source.clone(),
+            banned: false,
+            category_match: false,
+            ai_relevant: None,
+            ai_cached: false,
+            already_aired: false,
+            update_on: None,
+        }
+    }
+}
+
+/// One step of the article filter chain, stages run in order on the articles the
+/// previous ones kept.
+#[async_trait]
+pub trait FilterStage: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Slow stages get a progress bar.
+    fn show_progress(&self) -> bool {
+        false
+    }
+
+    /// Record the decision in `verdict` and return whether the article goes on.
+    async fn keep(&self, article: &Article, verdict: &mut Verdict) -> Result<bool>;
+
+    /// How the stage was worked around during the run, if it was.
+    fn degradation(&self) -> Option<Degradation> {
+        None
+    }
+}
@@ -0,0 +1,280 @@
+//! Articles seen and aired by previous runs, so a story is not broadcast twice.
+
+use std::{ path::Path, sync::Mutex };
+use chrono::{ Duration, Utc };
+use rusqlite::{ params, Connection, OptionalExtension };
+use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
+use url::Url;
+use crate::{ error::{ Error, Result }, filter::Verdict, types::Article };
+
+// Tracking parameters that make the same article look like several
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "xtor", "at_medium", "at_campaign", "ref"];
+
+/// What an earlier broadcast already told about a story.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreviousCoverage {
+    /// Broadcast the story aired in, `%Y-%m-%d-%H%M%S` like the output files.
+    pub broadcast_id: String,
+    pub title: String,
+    pub description: String,
+}
+
+/// Whether an article already aired, and in which version.
+pub enum Seen {
+    New,
+    /// Aired with the same content.
+    Aired,
+    /// Aired before, but the content changed since.
+    Updated(PreviousCoverage),
+}
+
+/// SQLite store of the articles seen by previous runs, keyed by canonical URL.
+pub struct History {
+    connection: Mutex<Connection>,
+}
+
+impl History {
+    /// Open or create the database, forgetting articles not seen for `retention_days`.
+    pub fn open(path: &Path, retention_days: u32) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection: Connection = Connection::open(path).map_err(history_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS articles (
+                    url TEXT PRIMARY KEY,
+                    content_hash TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    description TEXT NOT NULL,
+                    verdict TEXT,
+                    first_seen TEXT NOT NULL,
+                    last_seen TEXT NOT NULL,
+                    broadcast_id TEXT,
+                    aired_hash TEXT
+                );
+                CREATE INDEX IF NOT EXISTS articles_aired_hash ON articles (aired_hash);"
+            )
+            .map_err(history_error)?;
+
+        // Stored in UTC; datetime() also reads the local offsets written by older versions,
+        // a plain string compare would be off by the offset and around DST changes
+        let cutoff: String = (Utc::now() - Duration::days(retention_days.into())).to_rfc3339();
+        connection
+            .execute("DELETE FROM articles WHERE datetime(last_seen) < datetime(?1)", params![cutoff])
+            .map_err(history_error)?;
+
+        Ok(History { connection: Mutex::new(connection) })
+    }
+
+    /// Look the article up by URL, or by content for the same story under another URL.
+    pub fn seen(&self, article: &Article) -> Result<Seen> {
+        let hash: String = content_hash(article);
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        let aired: Option<(String, String, String, String)> = connection
+            .query_row(
+                "SELECT aired_hash, broadcast_id, title, description FROM articles
+                 WHERE broadcast_id IS NOT NULL AND (url = ?1 OR aired_hash = ?2)
+                 ORDER BY broadcast_id DESC LIMIT 1",
+                params![canonical_url(&article.source), hash],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            )
+            .optional()
+            .map_err(history_error)?;
+
+        Ok(match aired {
+            None => Seen::New,
+            Some((aired_hash, _, _, _)) if aired_hash == hash => Seen::Aired,
+            Some((_, broadcast_id, title, description)) =>
+                Seen::Updated(PreviousCoverage { broadcast_id, title, description }),
+        })
+    }
+
+    /// Remember every fetched article with what the filters decided about it.
+    pub fn record_seen(&self, articles: &[Article], verdicts: &[Verdict]) -> Result<()> {
+        let now: String = Utc::now().to_rfc3339();
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        let transaction = connection.transaction().map_err(history_error)?;
+        for (article, verdict) in articles.iter().zip(verdicts) {
+            transaction
+                .execute(
+                    "INSERT INTO articles (url, content_hash, title, description, verdict, first_seen, last_seen)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+                     ON CONFLICT (url) DO UPDATE SET
+                        content_hash = excluded.content_hash,
+                        verdict = excluded.verdict,
+                        last_seen = excluded.last_seen",
+                    params![
+                        canonical_url(&article.source),
+                        content_hash(article),
+                        article.title,
+                        article.description,
+                        serde_json::to_string(verdict)?,
+                        now
+                    ]
+                )
+                .map_err(history_error)?;
+        }
+        transaction.commit().map_err(history_error)
+    }
+
+    /// Mark the articles as aired in `broadcast_id`, with the content they aired with.
+    pub fn record_aired(&self, articles: &[&Article], broadcast_id: &str) -> Result<()> {
+        let now: String = Utc::now().to_rfc3339();
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        let transaction = connection.transaction().map_err(history_error)?;
+        for article in articles {
+            let hash: String = content_hash(article);
+            transaction
+                .execute(
+                    "INSERT INTO articles (url, content_hash, title, description, first_seen, last_seen, broadcast_id, aired_hash)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6, ?2)
+                     ON CONFLICT (url) DO UPDATE SET
+                        content_hash = excluded.content_hash,
+                        title = excluded.title,
+                        description = excluded.description,
+                        last_seen = excluded.last_seen,
+                        broadcast_id = excluded.broadcast_id,
+                        aired_hash = excluded.aired_hash",
+                    params![
+                        canonical_url(&article.source),
+                        hash,
+                        article.title,
+                        article.description,
+                        now,
+                        broadcast_id
+                    ]
+                )
+                .map_err(history_error)?;
+        }
+        transaction.commit().map_err(history_error)
+    }
+}
+
+fn history_error(e: rusqlite::Error) -> Error {
+    Error::Database(format!("article history: {}", e))
+}
+
+/// URL without fragment, tracking parameters, `www.` or trailing slash.
+pub fn canonical_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+    parsed.set_fragment(None);
+
+    let query: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if query.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(query);
+    }
+
+    if let Some(host) = parsed.host_str().and_then(|h: &str| h.strip_prefix("www.")).map(String::from) {
+        let _ = parsed.set_host(Some(&host));
+    }
+    let _ = parsed.set_scheme("https");
+    let path: String = parsed.path().trim_end_matches('/').to_string();
+    parsed.set_path(&path);
+
+    parsed.as_str().trim_end_matches('/').to_string()
+}
+
+/// Hash of the title and description, insensitive to case and whitespace.
+pub fn content_hash(article: &Article) -> String {
+    let normalized: String = format!("{}\n{}", article.title, article.description)
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    sha256_hex(&normalized)
+}
+
+pub(crate) fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte: &u8| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_url_drops_tracking_and_noise() {
+        assert_eq!(
+            canonical_url("http://www.example.com/news/story/?utm_source=rss&id=42&fbclid=x#top"),
+            "https://example.com/news/story?id=42"
+        );
+        assert_eq!(
+            canonical_url(" https://example.com/story/?xtor=RSS-1 "),
+            "https://example.com/story"
+        );
+        assert_eq!(canonical_url("https://example.com/"), "https://example.com");
+    }
+
+    #[test]
+    fn canonical_url_keeps_what_tells_articles_apart() {
+        assert_eq!(
+            canonical_url("https://example.com/story?page=2&id=42"),
+            "https://example.com/story?page=2&id=42"
+        );
+        assert_ne!(canonical_url("https://example.com/a"), canonical_url("https://example.com/b"));
+        assert_eq!(canonical_url("https://news.example.com/a"), "https://news.example.com/a");
+    }
+
+    #[test]
+    fn canonical_url_leaves_invalid_urls_alone() {
+        assert_eq!(canonical_url(" not a url "), "not a url");
+        assert_eq!(canonical_url(""), "");
+    }
+
+    #[test]
+    fn retention_reads_utc_and_local_timestamps() {
+        let path: std::path::PathBuf = std::env
+            ::temp_dir()
+            .join(format!("moriocho-history-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let history: History = History::open(&path, 7).unwrap();
+            let connection = history.connection.lock().unwrap();
+            // Same instant as UTC and as a local time with an offset, both expired or both not
+            let old: chrono::DateTime<Utc> = Utc::now() - Duration::days(8);
+            let recent: chrono::DateTime<Utc> = Utc::now() - Duration::hours(1);
+            let offset: chrono::FixedOffset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+            for (url, seen) in [
+                ("https://example.com/old-utc", old.to_rfc3339()),
+                ("https://example.com/old-local", old.with_timezone(&offset).to_rfc3339()),
+                ("https://example.com/recent-utc", recent.to_rfc3339()),
+                ("https://example.com/recent-local", recent.with_timezone(&offset).to_rfc3339()),
+            ] {
+                connection
+                    .execute(
+                        "INSERT INTO articles (url, content_hash, title, description, first_seen, last_seen)
+                         VALUES (?1, '', '', '', ?2, ?2)",
+                        params![url, seen]
+                    )
+                    .unwrap();
+            }
+        }
+
+        let history: History = History::open(&path, 7).unwrap();
+        let kept: Vec<String> = history.connection
+            .lock()
+            .unwrap()
+            .prepare("SELECT url FROM articles ORDER BY url")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .unwrap();
+        drop(history);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(kept, ["https://example.com/recent-local", "https://example.com/recent-utc"]);
+    }
+}
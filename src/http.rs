@@ -0,0 +1,33 @@
+//! Builds the single HTTP client every request goes through, from the `http` config.
+
+use std::time::Duration;
+use reqwest::{ Certificate, Client, Proxy };
+use crate::{ config::HttpConfig, error::{ Error, Result } };
+
+/// Client with the configured user agent, proxy, timeouts, pool and extra CA; cheap to clone,
+/// clones share the connection pool.
+pub fn build_client(http: &HttpConfig) -> Result<Client> {
+    let mut builder: reqwest::ClientBuilder = Client::builder()
+        .user_agent(&http.user_agent)
+        .timeout(Duration::from_secs(http.timeout_secs))
+        .connect_timeout(Duration::from_secs(http.connect_timeout_secs))
+        .pool_idle_timeout(Duration::from_secs(http.pool_idle_timeout_secs))
+        .pool_max_idle_per_host(http.pool_max_idle_per_host);
+
+    if let Some(proxy) = &http.proxy {
+        builder = builder.proxy(
+            Proxy::all(proxy).map_err(|e| Error::Config(format!("invalid `http.proxy`: {}", e)))?
+        );
+    }
+    if let Some(path) = &http.ca_certificate {
+        let pem: Vec<u8> = std::fs
+            ::read(path)
+            .map_err(|e| Error::Config(format!("failed to read {}: {}", path, e)))?;
+        let certificate: Certificate = Certificate::from_pem(&pem).map_err(|e|
+            Error::Config(format!("invalid certificate in {}: {}", path, e))
+        )?;
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    builder.build().map_err(|e| Error::Config(format!("failed to build the HTTP client: {}", e)))
+}
@@ -0,0 +1,27 @@
+//! Moriocho Radio: personalized morning news briefings.
+//!
+//! News feeds are fetched ([`fetch::news`]), filtered by keywords and an LLM ([`filter`],
+//! [`ai::filter`]), summarized into a [`ai::script::BroadcastScript`] along with the weather
+//! ([`fetch::weather`]), then written to [`output`] sinks and optionally read aloud ([`tts`],
+//! [`audio`]). [`pipeline::Pipeline`] ties the stages together.
+
+pub mod ai;
+pub mod audio;
+pub mod checkpoint;
+pub mod config;
+pub mod daemon;
+pub mod console;
+pub mod error;
+pub mod fetch;
+pub mod filter;
+pub mod history;
+pub mod http;
+pub mod output;
+pub mod pipeline;
+pub mod schedule;
+pub mod server;
+pub mod tts;
+
+pub use error::{ Error, Result };
+pub use fetch::types;
+pub use pipeline::{ Pipeline, PipelineBuilder };
@@ -1,172 +1,148 @@
-use std::error::Error;
-use ai::{ filter::ai_filter, resume::{ ai_resume, ai_resume_aggregate } };
-use fetch::{ news, types, weather };
-use types::WeatherResponse;
-use std::io::{ self, Write };
-use filter::{ banned::banned, category::category };
-mod ai;
-mod fetch;
-mod config;
-mod filter;
-use futures::stream::{ self, StreamExt };
-use reqwest::Client;
-use indicatif::{ ProgressBar, ProgressStyle };
+use std::io::{ self, IsTerminal, Write };
+use std::path::{ Path, PathBuf };
+use std::process::ExitCode;
+use clap::Parser;
+use moriocho_radio::{
+    checkpoint::RunDir,
+    config,
+    daemon::{ self, DaemonConfig, ProfileLock },
+    console,
+    error::{ Error, Result },
+    output::{ self, RunReport },
+    pipeline::{ read_stage, write_stage, FilterOutput, Pipeline, PipelineBuilder },
+    server,
+    types::Article,
+};
+mod cli;
+use cli::{ Cli, Command, SinkArgs };
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize shared resources
-    let client: Client = reqwest::Client::new();
-    let config: config::Config = config::load_config()?;
-
-    // Create progress style once
-    let progress_style: ProgressStyle = ProgressStyle::default_bar()
-        .template(
-            "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%)"
-        )
-        .unwrap();
-
-    // Fetch weather
-    println!("Fetching weather for {}...", config.city);
-    let weather: WeatherResponse = weather::fetch_weather(&config.city).await?;
-
-    // Fetch and filter articles
-    let news_sources: Vec<&str> = config.news_sources
-        .iter()
-        .map(|source: &config::Source| source.url.as_str())
-        .collect();
-
-    let articles: Vec<types::Article> = news::fetch_news(&news_sources).await?;
-
-    let mut filtered_articles: Vec<&types::Article> = Vec::new();
-
-    for article in &articles {
-        let banned: bool = banned(&article.title, &article.description, &config.filter).await?;
-        if !banned {
-            continue;
-        }
-
-        let category_match: bool = category(
-            &article.title,
-            &article.description,
-            &config.filter
-        ).await?;
-        if category_match {
-            filtered_articles.push(article);
-            continue;
+async fn main() -> ExitCode {
+    let cli: Cli = Cli::parse();
+    console::set_verbosity(cli.verbosity());
+
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e.user_message());
+            ExitCode::from(e.exit_code())
         }
-
-        filtered_articles.push(article);
     }
+}
 
-    println!("Filtering with AI...");
-    let filter_pb: ProgressBar = ProgressBar::new(filtered_articles.len() as u64).with_style(
-        progress_style.clone()
-    );
+async fn run(cli: &Cli) -> Result<()> {
+    if let Some(Command::Daemon { log_file }) = &cli.command {
+        return run_daemon(cli, log_file.as_deref()).await;
+    }
 
-    // Filtrage AI uniquement sur les articles restants
-    let ai_filtered_articles: Vec<&types::Article> = stream
-        ::iter(filtered_articles.iter())
-        .map(|article: &&types::Article| {
-            let client: &Client = &client;
-            let filter_pb: &ProgressBar = &filter_pb;
-            let filter_clone: config::FilterConfig = config.filter.clone();
-            let config_clone: config::Config = config.clone();
-            async move {
-                let is_relevant: bool = ai_filter(
-                    &article.title,
-                    &article.description,
-                    &config_clone,
-                    &filter_clone,
-                    client
-                ).await.unwrap_or(false);
-                filter_pb.inc(1);
-                if is_relevant {
-                    Some(*article)
-                } else {
-                    None
-                }
+    let mut config: config::Config = config::load_config_from(
+        cli.config.as_deref(),
+        cli.profile.as_deref()
+    )?;
+    if let Some(output_dir) = &cli.output_dir {
+        config.output_dir = output_dir.to_string_lossy().into_owned();
+    }
+    let output_dir: PathBuf = PathBuf::from(&config.output_dir);
+    let stage_file = |path: &Option<PathBuf>, name: &str| {
+        path.clone().unwrap_or_else(|| output_dir.join(name))
+    };
+
+    let default_command: Command = Command::Run { sinks: SinkArgs::default(), resume: None };
+    let command: &Command = cli.command.as_ref().unwrap_or(&default_command);
+
+    // `--sinks` replaces the sinks from the config
+    let sink_args: Option<&SinkArgs> = match command {
+        Command::Run { sinks, .. } | Command::Summarize { sinks, .. } => Some(sinks),
+        _ => None,
+    };
+    let mut builder: PipelineBuilder = Pipeline::builder(config.clone());
+    for kind in sink_args.and_then(|s: &SinkArgs| s.sinks.as_ref()).into_iter().flatten() {
+        builder = builder.sink(output::build_sink(*kind));
+    }
+    let pipeline: Pipeline = builder.build()?;
+
+    match command {
+        Command::Run { resume, .. } => {
+            let _lock: ProfileLock = daemon::lock_profile(&output_dir, cli.profile.as_deref())?;
+            let run: RunDir = match resume {
+                Some(id) => RunDir::open(&output_dir, id)?,
+                None => RunDir::create(&output_dir)?,
+            };
+            let report: RunReport = pipeline.run_with(&run).await.inspect_err(|_| {
+                eprintln!("Resume with: moriocho-radio run --resume {}", run.id());
+            })?;
+            println!("\nSummary:\n{}", report.script.to_plain_text());
+            print_degradations(&report);
+            pipeline.speak(&report.script).await;
+
+            // Keeps the console open when started by double-click
+            if !cli.non_interactive && io::stdin().is_terminal() {
+                let _ = io::stdout().flush();
+                let mut buffer: String = String::new();
+                let _ = io::stdin().read_line(&mut buffer);
             }
-        })
-        .buffer_unordered(1)
-        .filter_map(|x: Option<&types::Article>| async move { x })
-        .collect().await;
-
-    filter_pb.finish_with_message("AI filtering done");
-
-    // Fetch content for filtered articles
-    println!("Fetching article content...");
-    let fetch_pb: ProgressBar = ProgressBar::new(ai_filtered_articles.len() as u64).with_style(
-        progress_style
-    );
-
-    let articles_with_content: Vec<types::Article> = stream
-        ::iter(ai_filtered_articles)
-        .map(|article: &types::Article| {
-            let client: &Client = &client;
-            let fetch_pb: &ProgressBar = &fetch_pb;
-            async move {
-                if !article.source.is_empty() {
-                    let result: Result<types::Article, Box<dyn Error>> = news::fetch_article(
-                        &article.title,
-                        &article.source,
-                        &article.description,
-                        client
-                    ).await;
-                    fetch_pb.inc(1);
-                    result.ok()
-                } else {
-                    fetch_pb.inc(1);
-                    None
-                }
+        }
+        Command::Fetch { output } => {
+            let articles: Vec<Article> = pipeline.fetch().await?;
+            write_stage(&stage_file(output, "articles.json"), &articles)?;
+        }
+        Command::Filter(stage) => {
+            let articles: Vec<Article> = read_stage(&stage_file(&stage.input, "articles.json"))?;
+            let filtered: FilterOutput = pipeline.filter(&articles).await?;
+            write_stage(&stage_file(&stage.output, "kept.json"), &filtered)?;
+        }
+        Command::Summarize { stage, .. } => {
+            let filtered: FilterOutput = read_stage(&stage_file(&stage.input, "kept.json"))?;
+            let report: RunReport = pipeline.summarize(filtered).await?;
+            write_stage(&stage_file(&stage.output, "script.json"), &report)?;
+            pipeline.publish(&report);
+            print_degradations(&report);
+        }
+        Command::Daemon { .. } => unreachable!("handled above"),
+        Command::Serve { listen } => {
+            server::serve(config, cli.profile.clone(), *listen).await?;
+        }
+        Command::Speak { input } => {
+            let report: RunReport = read_stage(&stage_file(input, "script.json"))?;
+            if pipeline.speak(&report.script).await.is_none() {
+                return Err(Error::Tts("no audio produced".into()));
             }
-        })
-        .buffer_unordered(25)
-        .filter_map(|x: Option<types::Article>| async move { x })
-        .collect().await;
-
-    fetch_pb.finish_with_message("Content fetched");
-
-    // Generate summary
-    let articles_text: String = articles_with_content
-        .iter()
-        .map(|a: &types::Article|
-            format!(
-                "Title: {}\nDescription: {}\nContent: {}\n---\n",
-                a.title,
-                a.description,
-                a.content
-            )
-        )
-        .collect::<String>();
-
-    fn chunk_text(text: &str, max_len: usize) -> Vec<String> {
-        let mut chunks: Vec<String> = Vec::new();
-        let mut start: usize = 0;
-        while start < text.len() {
-            let end: usize = (start + max_len).min(text.len());
-            chunks.push(text[start..end].to_string());
-            start += max_len;
         }
-        chunks
     }
 
-    println!("Generating summary...");
-    let max_chunk_size: usize = 10000;
-    let article_chunks: Vec<String> = chunk_text(&articles_text, max_chunk_size);
-
-    let mut partial_summaries: Vec<String> = Vec::new();
-    for chunk in article_chunks {
-        let s: String = ai_resume_aggregate(&chunk, &client, &config).await?;
-        partial_summaries.push(s);
-    }
+    Ok(())
+}
 
-    let consolidated_summary: String = partial_summaries.join("\n");
+async fn run_daemon(cli: &Cli, log_file: Option<&Path>) -> Result<()> {
+    let daemon_config: DaemonConfig = DaemonConfig {
+        config_path: cli.config.clone(),
+        output_dir: cli.output_dir.as_ref().map(|dir: &PathBuf| dir.to_string_lossy().into_owned()),
+    };
+    let profiles: Vec<Option<String>> = match &cli.profile {
+        Some(profile) => vec![Some(profile.clone())],
+        None => daemon_config.scheduled_profiles()?,
+    };
+
+    let log_file: PathBuf = match log_file {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let config: config::Config = config::load_config_from(cli.config.as_deref(), None)?;
+            let output_dir: String = daemon_config.output_dir.clone().unwrap_or(config.output_dir);
+            Path::new(&output_dir).join("daemon.log")
+        }
+    };
+    println!("Logging to {}", log_file.display());
+    console::log_to_file(&log_file)?;
 
-    let final_summary: String = ai_resume(&weather, &consolidated_summary, &client, &config).await?;
-    println!("\nSummary:\n{}", final_summary);
+    daemon::run(&daemon_config, &profiles).await
+}
 
-    let _ = io::stdout().flush();
-    let mut buffer: String = String::new();
-    let _ = io::stdin().read_line(&mut buffer);
-    Ok(())
+fn print_degradations(report: &RunReport) {
+    if report.degradations.is_empty() {
+        return;
+    }
+    eprintln!("\nDegraded:");
+    for degradation in &report.degradations {
+        eprintln!("  - {}", degradation);
+    }
 }
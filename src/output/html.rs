@@ -0,0 +1,76 @@
+use crate::error::Result;
+use super::{ escape_html, RunReport, Sink };
+
+// Standalone newsletter, styles are inlined so it survives mail clients
+pub struct HtmlSink;
+
+const STYLE: &str =
+    "body{font-family:Georgia,serif;max-width:640px;margin:2em auto;padding:0 1em;color:#222;line-height:1.5}\
+    h1{font-size:1.6em;border-bottom:2px solid #333}\
+    .weather{background:#eef5fb;border-radius:8px;padding:1em;margin:1em 0}\
+    .weather .temp{font-size:2em;font-weight:bold}\
+    .story h2{font-size:1.2em;margin-bottom:.2em}\
+    .sources a{font-size:.85em;color:#1a5d99;margin-right:.8em}";
+
+impl Sink for HtmlSink {
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn render(&self, report: &RunReport) -> Result<String> {
+        let script = &report.script;
+        let date: String = report.generated_at.format("%Y-%m-%d").to_string();
+
+        let mut html: String = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Morioh-cho Radio - {}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>Morioh-cho Radio - {}</h1>\n<p>{}</p>\n",
+            date,
+            STYLE,
+            date,
+            escape_html(&script.intro)
+        );
+
+        if let Some(weather) = &report.weather {
+            html.push_str(
+                &format!(
+                    "<div class=\"weather\">\n<div>{}</div>\n<div class=\"temp\">{}°C</div>\n<div>{}</div>\n<div>{}</div>\n<p>{}</p>\n</div>\n",
+                    escape_html(&weather.city),
+                    weather.current.temperature,
+                    escape_html(weather.current.get_weather_description(&report.language_code)),
+                    escape_html(&weather.get_day_forecast(&report.language_code)),
+                    escape_html(&script.weather)
+                )
+            );
+        } else if !script.weather.is_empty() {
+            html.push_str(
+                &format!("<div class=\"weather\">\n<p>{}</p>\n</div>\n", escape_html(&script.weather))
+            );
+        }
+        if !report.elsewhere.is_empty() {
+            html.push_str("<ul class=\"elsewhere\">\n");
+            for other in &report.elsewhere {
+                let line: String = other.get_day_forecast(&report.language_code);
+                html.push_str(&format!("<li>{}</li>\n", escape_html(&line)));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        for segment in &script.news {
+            html.push_str(
+                &format!(
+                    "<div class=\"story\">\n<h2>{}</h2>\n<p>{}</p>\n<div class=\"sources\">",
+                    escape_html(&segment.headline),
+                    escape_html(&segment.body)
+                )
+            );
+            for (i, source) in segment.sources.iter().enumerate() {
+                html.push_str(
+                    &format!("<a href=\"{}\">Source {}</a>", escape_html(source), i + 1)
+                );
+            }
+            html.push_str("</div>\n</div>\n");
+        }
+
+        html.push_str(&format!("<p>{}</p>\n</body>\n</html>\n", escape_html(&script.outro)));
+        Ok(html)
+    }
+}
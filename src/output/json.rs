@@ -0,0 +1,15 @@
+use crate::error::Result;
+use super::{ RunReport, Sink };
+
+// The full run: articles, filter verdicts, weather and script
+pub struct JsonSink;
+
+impl Sink for JsonSink {
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn render(&self, report: &RunReport) -> Result<String> {
+        Ok(serde_json::to_string_pretty(report)?)
+    }
+}
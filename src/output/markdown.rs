@@ -0,0 +1,56 @@
+use crate::error::Result;
+use super::{ RunReport, Sink };
+
+pub struct MarkdownSink;
+
+impl Sink for MarkdownSink {
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn render(&self, report: &RunReport) -> Result<String> {
+        let script = &report.script;
+        let mut md: String = format!(
+            "# Morioh-cho Radio - {}\n\n{}\n\n",
+            report.generated_at.format("%Y-%m-%d"),
+            script.intro
+        );
+
+        if let Some(weather) = &report.weather {
+            md.push_str(
+                &format!(
+                    "## Weather in {}\n\n> **{}°C**, {}  \n> {}\n\n{}\n\n",
+                    weather.city,
+                    weather.current.temperature,
+                    weather.current.get_weather_description(&report.language_code),
+                    weather.get_day_forecast(&report.language_code),
+                    script.weather
+                )
+            );
+        } else if !script.weather.is_empty() {
+            md.push_str(&format!("## Weather\n\n{}\n\n", script.weather));
+        }
+        for other in &report.elsewhere {
+            md.push_str(&format!("- {}\n", other.get_day_forecast(&report.language_code)));
+        }
+        if !report.elsewhere.is_empty() {
+            md.push('\n');
+        }
+
+        if !script.news.is_empty() {
+            md.push_str("## News\n\n");
+        }
+        for segment in &script.news {
+            md.push_str(&format!("### {}\n\n{}\n\n", segment.headline, segment.body));
+            for (i, source) in segment.sources.iter().enumerate() {
+                md.push_str(&format!("[Source {}]({}) ", i + 1, source));
+            }
+            if !segment.sources.is_empty() {
+                md.push_str("\n\n");
+            }
+        }
+
+        md.push_str(&format!("{}\n", script.outro));
+        Ok(md)
+    }
+}
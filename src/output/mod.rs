@@ -0,0 +1,125 @@
+pub mod podcast;
+pub mod markdown;
+pub mod html;
+pub mod text;
+pub mod json;
+
+use std::path::{ Path, PathBuf };
+use chrono::{ DateTime, Local };
+use serde::{ Deserialize, Serialize };
+use crate::{
+    ai::script::{ BroadcastScript, NewsSegment },
+    config::SinkKind,
+    error::Result,
+    filter::Verdict,
+    types::{ Article, Forecast },
+};
+
+// Everything a run produced, handed to each sink
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunReport {
+    pub generated_at: DateTime<Local>,
+    /// ISO 639-1 code the script is written in, for the weather text the sinks add.
+    #[serde(default = "default_language_code")]
+    pub language_code: String,
+    /// `None` when the weather stage was skipped.
+    pub weather: Option<Forecast>,
+    /// The other configured locations whose forecast could be fetched.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elsewhere: Vec<Forecast>,
+    pub articles: Vec<Article>,
+    pub verdicts: Vec<Verdict>,
+    pub script: BroadcastScript,
+    /// Stages that failed and were worked around.
+    #[serde(default)]
+    pub degradations: Vec<Degradation>,
+}
+
+// Reports saved before the field existed were written for English
+fn default_language_code() -> String {
+    "en".into()
+}
+
+impl RunReport {
+    /// Identifies the broadcast in file names and the article history.
+    pub fn broadcast_id(&self) -> String {
+        self.generated_at.format("%Y-%m-%d-%H%M%S").to_string()
+    }
+
+    /// Articles the script tells about; all of them when the script cites no source.
+    pub fn aired_articles(&self) -> Vec<&Article> {
+        let cited: Vec<&str> = self.script.news
+            .iter()
+            .flat_map(|segment: &NewsSegment| segment.sources.iter().map(String::as_str))
+            .collect();
+        self.articles
+            .iter()
+            .filter(|a: &&Article| cited.is_empty() || cited.contains(&a.source.as_str()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Degradation {
+    WeatherSkipped {
+        reason: String,
+    },
+    HeadlineOnly {
+        articles: usize,
+    },
+    ExtractiveSummary {
+        reason: String,
+    },
+    AiFilterSkipped {
+        articles: usize,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for Degradation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Degradation::WeatherSkipped { reason } => write!(f, "weather skipped ({})", reason),
+            Degradation::HeadlineOnly { articles } =>
+                write!(f, "{} article(s) summarised from their headline only", articles),
+            Degradation::ExtractiveSummary { reason } =>
+                write!(f, "extractive summary used instead of the LLM ({})", reason),
+            Degradation::AiFilterSkipped { articles, reason } =>
+                write!(f, "{} article(s) kept without the AI filter ({})", articles, reason),
+        }
+    }
+}
+
+pub trait Sink: Send + Sync {
+    fn extension(&self) -> &'static str;
+
+    fn render(&self, report: &RunReport) -> Result<String>;
+
+    // One file per run, named after the run time
+    fn write(&self, report: &RunReport, directory: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(directory)?;
+        let path: PathBuf = directory.join(
+            format!("broadcast-{}.{}", report.broadcast_id(), self.extension())
+        );
+        std::fs::write(&path, self.render(report)?)?;
+        Ok(path)
+    }
+}
+
+pub fn build_sink(kind: SinkKind) -> Box<dyn Sink> {
+    match kind {
+        SinkKind::Markdown => Box::new(markdown::MarkdownSink),
+        SinkKind::Html => Box::new(html::HtmlSink),
+        SinkKind::Text => Box::new(text::TextSink),
+        SinkKind::Json => Box::new(json::JsonSink),
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,209 @@
+use std::{ collections::BTreeMap, fs::File, io::BufReader, path::{ Path, PathBuf } };
+use chrono::{ DateTime, Local };
+use rss::{
+    extension::itunes::{
+        self,
+        ITunesCategoryBuilder,
+        ITunesChannelExtensionBuilder,
+        ITunesItemExtensionBuilder,
+        ITunesOwnerBuilder,
+    },
+    validation::Validate,
+    Channel,
+    ChannelBuilder,
+    EnclosureBuilder,
+    GuidBuilder,
+    Item,
+    ItemBuilder,
+};
+use crate::{
+    ai::script::BroadcastScript,
+    config::{ Config, PodcastConfig },
+    console,
+    error::{ Error, Result },
+};
+use super::escape_html;
+
+const FEED_FILE: &str = "feed.xml";
+const EPISODES_DIR: &str = "episodes";
+
+fn mime_type(path: &Path) -> &'static str {
+    match
+        path
+            .extension()
+            .and_then(|e: &std::ffi::OsStr| e.to_str())
+            .map(|e: &str| e.to_lowercase())
+            .as_deref()
+    {
+        Some("mp3") => "audio/mpeg",
+        Some("ogg" | "oga" | "opus") => "audio/ogg",
+        Some("m4a" | "aac") => "audio/mp4",
+        _ => "audio/wav",
+    }
+}
+
+// HH:MM:SS, only known without decoding for WAV files
+fn duration(path: &Path) -> Option<String> {
+    let reader: hound::WavReader<_> = hound::WavReader::open(path).ok()?;
+    let seconds: u32 = reader.duration() / reader.spec().sample_rate;
+    Some(format!("{:02}:{:02}:{:02}", seconds / 3600, (seconds / 60) % 60, seconds % 60))
+}
+
+fn show_notes(script: &BroadcastScript) -> String {
+    let mut notes: String = format!("<p>{}</p>", escape_html(&script.weather));
+    if script.news.is_empty() {
+        return notes;
+    }
+    notes.push_str("<ul>");
+    for segment in &script.news {
+        notes.push_str(&format!("<li><strong>{}</strong>", escape_html(&segment.headline)));
+        for source in &segment.sources {
+            notes.push_str(
+                &format!(" <a href=\"{}\">{}</a>", escape_html(source), escape_html(source))
+            );
+        }
+        notes.push_str("</li>");
+    }
+    notes.push_str("</ul>");
+    notes
+}
+
+// iTunes summaries are plain text
+fn summary(script: &BroadcastScript) -> String {
+    let mut lines: Vec<&str> = vec![script.weather.as_str()];
+    lines.extend(script.news.iter().map(|s: &crate::ai::script::NewsSegment| s.headline.as_str()));
+    lines.retain(|l: &&str| !l.is_empty());
+    lines.join("\n")
+}
+
+fn new_channel(podcast: &PodcastConfig, config: &Config) -> Channel {
+    let mut namespaces: BTreeMap<String, String> = BTreeMap::new();
+    namespaces.insert("itunes".into(), itunes::NAMESPACE.into());
+
+    ChannelBuilder::default()
+        .title(podcast.title.clone())
+        .link(podcast.base_url.clone())
+        .description(podcast.description.clone())
+        .language(Some(config.language_code().to_string()))
+        .namespaces(namespaces)
+        .itunes_ext(
+            Some(
+                ITunesChannelExtensionBuilder::default()
+                    .author(Some(podcast.author.clone()))
+                    .summary(Some(podcast.description.clone()))
+                    .image(podcast.image.clone())
+                    .explicit(Some("false".into()))
+                    .categories(vec![ITunesCategoryBuilder::default().text("News").build()])
+                    .owner(Some(ITunesOwnerBuilder::default().name(Some(podcast.author.clone())).build()))
+                    .build()
+            )
+        )
+        .build()
+}
+
+/// The podcast feed file, in the podcast `directory`.
+pub fn feed_path(podcast: &PodcastConfig) -> PathBuf {
+    Path::new(&podcast.directory).join(FEED_FILE)
+}
+
+// Copy the episode audio next to the feed and add it on top of `feed.xml`
+pub fn publish_episode(
+    audio: &Path,
+    script: &BroadcastScript,
+    podcast: &PodcastConfig,
+    config: &Config
+) -> Result<PathBuf> {
+    let directory: &Path = Path::new(&podcast.directory);
+    std::fs::create_dir_all(directory.join(EPISODES_DIR))?;
+
+    let now: DateTime<Local> = Local::now();
+    let extension: &str = audio
+        .extension()
+        .and_then(|e: &std::ffi::OsStr| e.to_str())
+        .unwrap_or("wav");
+    let file_name: String = format!("{}.{}", now.format("%Y-%m-%d-%H%M%S"), extension);
+    let episode_path: PathBuf = directory.join(EPISODES_DIR).join(&file_name);
+    std::fs::copy(audio, &episode_path)?;
+
+    // The channel follows the current config, only the episodes are carried over
+    let feed_path: PathBuf = feed_path(podcast);
+    let previous: Vec<Item> = if feed_path.exists() {
+        Channel::read_from(BufReader::new(File::open(&feed_path)?))
+            .map_err(|e|
+                Error::Output(format!("invalid existing feed {}: {}", feed_path.display(), e))
+            )?
+            .into_items()
+    } else {
+        Vec::new()
+    };
+    let mut channel: Channel = new_channel(podcast, config);
+
+    let episode_url: String = format!(
+        "{}/{}/{}",
+        podcast.base_url.trim_end_matches('/'),
+        EPISODES_DIR,
+        file_name
+    );
+    let title: String = format!("{} - {}", podcast.title, now.format("%Y-%m-%d"));
+    let notes: String = show_notes(script);
+
+    let item: Item = ItemBuilder::default()
+        .title(Some(title))
+        .description(Some(notes.clone()))
+        .pub_date(Some(now.to_rfc2822()))
+        .guid(Some(GuidBuilder::default().value(episode_url.clone()).permalink(false).build()))
+        .enclosure(
+            Some(
+                EnclosureBuilder::default()
+                    .url(episode_url)
+                    .length(std::fs::metadata(&episode_path)?.len().to_string())
+                    .mime_type(mime_type(&episode_path).to_string())
+                    .build()
+            )
+        )
+        .itunes_ext(
+            Some(
+                ITunesItemExtensionBuilder::default()
+                    .summary(Some(summary(script)))
+                    .duration(duration(&episode_path))
+                    .episode_type(Some("full".into()))
+                    .explicit(Some("false".into()))
+                    .build()
+            )
+        )
+        .build();
+
+    let mut items: Vec<Item> = vec![item.clone()];
+    items.extend(previous.into_iter().filter(|i: &Item| i.guid() != item.guid()));
+    // The new episode always stays, even with `max_episodes: 0`
+    let keep: usize = podcast.max_episodes.max(1).min(items.len());
+    let dropped: Vec<Item> = items.split_off(keep);
+    channel.set_items(items);
+    channel.set_last_build_date(Some(now.to_rfc2822()));
+
+    channel.validate().map_err(|e| Error::Output(format!("invalid podcast feed: {}", e)))?;
+    // Written aside then renamed, podcast clients never fetch a half written feed
+    let partial: PathBuf = feed_path.with_extension("xml.partial");
+    channel
+        .pretty_write_to(File::create(&partial)?, b' ', 2)
+        .map_err(|e| Error::Output(format!("failed to write {}: {}", partial.display(), e)))?;
+    std::fs::rename(&partial, &feed_path)?;
+
+    // Episodes that left the feed are no longer served
+    for path in dropped.iter().filter_map(|i: &Item| episode_file(directory, i)) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                console::warn(format!("Failed to delete {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    Ok(episode_path)
+}
+
+// Local audio of an item, found by the file name at the end of its enclosure URL
+fn episode_file(directory: &Path, item: &Item) -> Option<PathBuf> {
+    let url: &str = item.enclosure()?.url();
+    let name: &std::ffi::OsStr = Path::new(url.rsplit('/').next()?).file_name()?;
+    Some(directory.join(EPISODES_DIR).join(name))
+}
@@ -0,0 +1,14 @@
+use crate::error::Result;
+use super::{ RunReport, Sink };
+
+pub struct TextSink;
+
+impl Sink for TextSink {
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn render(&self, report: &RunReport) -> Result<String> {
+        Ok(format!("{}\n", report.script.to_plain_text()))
+    }
+}
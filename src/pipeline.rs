@@ -0,0 +1,605 @@
+use std::{ path::{ Path, PathBuf }, sync::Arc };
+use futures::stream::{ self, StreamExt };
+use indicatif::ProgressBar;
+use reqwest::Client;
+use serde::{ de::DeserializeOwned, Deserialize, Serialize };
+use crate::{
+    ai::{
+        cache::VerdictCache,
+        filter::AiFilter,
+        provider::{ LlmProvider, OpenAiCompatible },
+        extractive::ExtractiveSummarizer,
+        resume::LlmSummarizer,
+        script::BroadcastScript,
+        Summarizer,
+    },
+    audio,
+    checkpoint::RunDir,
+    config::{ self, Config, DegradationPolicy, SummarizerKind },
+    console,
+    error::{ Error, Result },
+    filter::{
+        banned::BannedFilter,
+        category::CategoryFilter,
+        history::HistoryFilter,
+        FilterStage,
+        Verdict,
+    },
+    history::History,
+    http,
+    output::{ self, Degradation, RunReport, Sink },
+    tts,
+    types::{ Article, Forecast },
+    fetch::{ news, weather::{ self, WeatherProvider } },
+};
+
+/// Output of the filter stage, exchanged as kept.json.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FilterOutput {
+    pub kept: Vec<Article>,
+    /// One entry per input article, in input order.
+    pub verdicts: Vec<Verdict>,
+    /// Filter stages that failed and were worked around.
+    #[serde(default)]
+    pub degradations: Vec<Degradation>,
+}
+
+// Checkpoint of the content fetch, before the degradation policy decides about failures
+#[derive(Serialize, Deserialize)]
+struct FetchedContents {
+    fetched: Vec<Article>,
+    failed: Vec<Article>,
+}
+
+/// The news pipeline: fetch, filter, summarize, then hand the result to sinks and TTS.
+///
+/// ```no_run
+/// # async fn run() -> moriocho_radio::Result<()> {
+/// use moriocho_radio::{ config, pipeline::Pipeline };
+///
+/// let config = config::load_config_from(None, None)?;
+/// let report = Pipeline::builder(config).build()?.run().await?;
+/// println!("{}", report.script.to_plain_text());
+/// # Ok(())
+/// # }
+/// ```
+pub struct Pipeline {
+    config: Config,
+    client: Client,
+    sources: Vec<String>,
+    stages: Vec<Box<dyn FilterStage>>,
+    summarizer: Arc<dyn Summarizer>,
+    weather: Arc<dyn WeatherProvider>,
+    history: Option<Arc<History>>,
+    sinks: Vec<Box<dyn Sink>>,
+}
+
+/// Builds a [`Pipeline`], anything not set explicitly comes from the [`Config`].
+pub struct PipelineBuilder {
+    config: Config,
+    client: Option<Client>,
+    sources: Option<Vec<String>>,
+    stages: Option<Vec<Box<dyn FilterStage>>>,
+    llm: Option<Arc<dyn LlmProvider>>,
+    summarizer: Option<Arc<dyn Summarizer>>,
+    weather: Option<Arc<dyn WeatherProvider>>,
+    history: Option<Arc<History>>,
+    sinks: Option<Vec<Box<dyn Sink>>>,
+}
+
+impl PipelineBuilder {
+    /// HTTP client for every request, replaces the one built from the `http` config.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// RSS feed URLs, replaces `news_sources` from the config.
+    pub fn sources(mut self, sources: Vec<String>) -> Self {
+        self.sources = Some(sources);
+        self
+    }
+
+    /// Adds a filter stage; once called, the default banned/category/AI chain is not used.
+    pub fn filter_stage(mut self, stage: Box<dyn FilterStage>) -> Self {
+        self.stages.get_or_insert_with(Vec::new).push(stage);
+        self
+    }
+
+    pub fn llm(mut self, llm: Arc<dyn LlmProvider>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    /// Writes the broadcast script, replaces `summarizer` from the config.
+    pub fn summarizer(mut self, summarizer: Arc<dyn Summarizer>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// Forecast source, replaces `weather.provider` from the config.
+    pub fn weather(mut self, weather: Arc<dyn WeatherProvider>) -> Self {
+        self.weather = Some(weather);
+        self
+    }
+
+    /// Article history, replaces the database from the `history` config.
+    pub fn history(mut self, history: Arc<History>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Adds an output sink; once called, `sinks` from the config is not used.
+    pub fn sink(mut self, sink: Box<dyn Sink>) -> Self {
+        self.sinks.get_or_insert_with(Vec::new).push(sink);
+        self
+    }
+
+    /// Fails when the HTTP client or the weather provider can't be built from the config.
+    pub fn build(self) -> Result<Pipeline> {
+        let config: Config = self.config;
+        let client: Client = match self.client {
+            Some(client) => client,
+            None => http::build_client(&config.http)?,
+        };
+        let llm: Arc<dyn LlmProvider> = self.llm.unwrap_or_else(||
+            Arc::new(OpenAiCompatible::from_config(&config, client.clone()))
+        );
+        let sources: Vec<String> = self.sources.unwrap_or_else(||
+            config.news_sources
+                .iter()
+                .map(|source: &config::Source| source.url.clone())
+                .collect()
+        );
+        let history: Option<Arc<History>> = self.history.or_else(|| {
+            let history_config: &config::HistoryConfig = config.history.as_ref()?;
+            let path: PathBuf = Path::new(&config.output_dir).join(&history_config.path);
+            match History::open(&path, history_config.retention_days) {
+                Ok(history) => Some(Arc::new(history)),
+                Err(e) => {
+                    console::warn(format!("Article history disabled: {}", e));
+                    None
+                }
+            }
+        });
+        let offline: bool = config.summarizer == SummarizerKind::Extractive;
+        let stages: Vec<Box<dyn FilterStage>> = self.stages.unwrap_or_else(|| {
+            let mut stages: Vec<Box<dyn FilterStage>> = Vec::new();
+            // First, so stories that already aired skip the slow AI filter
+            if let Some(history) = &history {
+                stages.push(
+                    Box::new(HistoryFilter {
+                        history: history.clone(),
+                        updates: config.history.as_ref().is_none_or(|h: &config::HistoryConfig| h.updates),
+                    })
+                );
+            }
+            stages.push(Box::new(BannedFilter { filter: config.filter.clone() }));
+            stages.push(Box::new(CategoryFilter { filter: config.filter.clone() }));
+            if !offline {
+                let mut ai_filter: AiFilter = AiFilter::new(config.filter.clone(), llm.clone());
+                if let Some(cache) = open_verdict_cache(&config) {
+                    ai_filter = ai_filter.with_cache(cache);
+                }
+                stages.push(Box::new(ai_filter));
+            }
+            stages
+        });
+        let summarizer: Arc<dyn Summarizer> = self.summarizer.unwrap_or_else(||
+            match config.summarizer {
+                SummarizerKind::Llm => Arc::new(LlmSummarizer::new(llm.clone())),
+                SummarizerKind::Extractive => Arc::new(ExtractiveSummarizer),
+            }
+        );
+        let weather: Arc<dyn WeatherProvider> = match self.weather {
+            Some(weather) => weather,
+            None => weather::build_provider(&config, &client)?,
+        };
+        let sinks: Vec<Box<dyn Sink>> = self.sinks.unwrap_or_else(||
+            config.sinks.iter().copied().map(output::build_sink).collect()
+        );
+
+        Ok(Pipeline { config, client, sources, stages, summarizer, weather, history, sinks })
+    }
+}
+
+impl Pipeline {
+    pub fn builder(config: Config) -> PipelineBuilder {
+        PipelineBuilder {
+            config,
+            client: None,
+            sources: None,
+            stages: None,
+            llm: None,
+            summarizer: None,
+            weather: None,
+            history: None,
+            sinks: None,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Fetch, filter and summarize, then write every sink; checkpoints go to a new run
+    /// directory under `output_dir/runs`.
+    pub async fn run(&self) -> Result<RunReport> {
+        let run: RunDir = RunDir::create(Path::new(&self.config.output_dir))?;
+        self.run_with(&run).await
+    }
+
+    /// Like [`Pipeline::run`], checkpointing each step in `run` and skipping the steps it
+    /// already holds, so an interrupted run can be resumed.
+    pub async fn run_with(&self, run: &RunDir) -> Result<RunReport> {
+        console::status(format!("Run {}", run.id()));
+
+        if let Some(report) = run.load::<RunReport>("script.json")? {
+            console::status("Resuming: broadcast already written, publishing it again");
+            self.publish(&report);
+            return Ok(report);
+        }
+
+        let articles: Vec<Article> = match run.load("articles.json")? {
+            Some(articles) => {
+                console::status("Resuming: articles already fetched");
+                articles
+            }
+            None => {
+                let articles: Vec<Article> = self.fetch().await?;
+                run.save("articles.json", &articles)?;
+                articles
+            }
+        };
+
+        let filtered: FilterOutput = match run.load("kept.json")? {
+            Some(filtered) => {
+                console::status("Resuming: articles already filtered");
+                filtered
+            }
+            None => {
+                let filtered: FilterOutput = self.filter(&articles).await?;
+                run.save("kept.json", &filtered)?;
+                filtered
+            }
+        };
+
+        let report: RunReport = self.summarize_with(filtered, Some(run)).await?;
+        run.save("script.json", &report)?;
+        self.publish(&report);
+        Ok(report)
+    }
+
+    /// Articles of the last 24 hours from every source, deduplicated by title.
+    pub async fn fetch(&self) -> Result<Vec<Article>> {
+        let sources: Vec<&str> = self.sources
+            .iter()
+            .map(|s: &String| s.as_str())
+            .collect();
+        news::fetch_news(&sources, &self.client).await
+    }
+
+    /// Run the filter stages in order.
+    pub async fn filter(&self, articles: &[Article]) -> Result<FilterOutput> {
+        let mut verdicts: Vec<Verdict> = articles.iter().map(Verdict::new).collect();
+        let mut remaining: Vec<usize> = (0..articles.len()).collect();
+
+        for stage in &self.stages {
+            let pb: ProgressBar = if stage.show_progress() {
+                console::status(format!("Filtering with {}...", stage.name()));
+                console::progress_bar(remaining.len())
+            } else {
+                ProgressBar::hidden()
+            };
+
+            let mut kept: Vec<usize> = Vec::new();
+            for i in remaining {
+                if stage.keep(&articles[i], &mut verdicts[i]).await? {
+                    kept.push(i);
+                }
+                pb.inc(1);
+            }
+            pb.finish_with_message(format!("{} filtering done", stage.name()));
+            remaining = kept;
+        }
+
+        if let Some(history) = &self.history {
+            if let Err(e) = history.record_seen(articles, &verdicts) {
+                console::warn(format!("Failed to update the article history: {}", e));
+            }
+        }
+
+        Ok(FilterOutput {
+            kept: remaining
+                .into_iter()
+                .map(|i: usize| Article {
+                    update_on: verdicts[i].update_on.clone(),
+                    ..articles[i].clone()
+                })
+                .collect(),
+            verdicts,
+            degradations: self.stages
+                .iter()
+                .filter_map(|stage| stage.degradation())
+                .collect(),
+        })
+    }
+
+    /// Download the body of each article; returns the articles with content and the ones that failed.
+    pub async fn fetch_contents(&self, articles: &[Article]) -> (Vec<Article>, Vec<Article>) {
+        let fetch_pb: ProgressBar = console::progress_bar(articles.len());
+
+        // Built up front like in `news::fetch_news`, for `Pipeline::run` to be spawnable
+        let fetches: Vec<_> = articles
+            .iter()
+            .map(|article: &Article| {
+                let client: &Client = &self.client;
+                let fetch_pb: &ProgressBar = &fetch_pb;
+                async move {
+                    let result: std::result::Result<Article, Article> = if article.source.is_empty() {
+                        Err(article.clone())
+                    } else {
+                        news::fetch_article(
+                            &article.title,
+                            &article.source,
+                            &article.date,
+                            &article.description,
+                            client
+                        ).await
+                            .map(|fetched: Article| Article {
+                                update_on: article.update_on.clone(),
+                                ..fetched
+                            })
+                            .map_err(|e: Error| {
+                            if console::verbose() {
+                                console::warn(e);
+                            }
+                            article.clone()
+                        })
+                    };
+                    fetch_pb.inc(1);
+                    result
+                }
+            })
+            .collect();
+        let results: Vec<std::result::Result<Article, Article>> = stream
+            ::iter(fetches)
+            .buffer_unordered(25)
+            .collect().await;
+
+        fetch_pb.finish_with_message("Content fetched");
+        let mut fetched: Vec<Article> = Vec::new();
+        let mut failed: Vec<Article> = Vec::new();
+        for result in results {
+            match result {
+                Ok(article) => fetched.push(article),
+                Err(article) => failed.push(article),
+            }
+        }
+        (fetched, failed)
+    }
+
+    /// Weather, article contents and the broadcast script for the kept articles.
+    ///
+    /// Failures are worked around according to the config's `degradation` policy and listed
+    /// in [`RunReport::degradations`].
+    pub async fn summarize(&self, filtered: FilterOutput) -> Result<RunReport> {
+        self.summarize_with(filtered, None).await
+    }
+
+    async fn summarize_with(&self, filtered: FilterOutput, run: Option<&RunDir>) -> Result<RunReport> {
+        let policy: &DegradationPolicy = &self.config.degradation;
+        let mut degradations: Vec<Degradation> = filtered.degradations.clone();
+
+        let location: &config::Location = &self.config.location;
+        let names: Vec<&str> = std::iter
+            ::once(location)
+            .chain(&self.config.locations)
+            .map(config::Location::display_name)
+            .collect();
+        console::status(
+            format!("Fetching weather for {} from {}...", names.join(", "), self.weather.name())
+        );
+        // All locations at once, the primary one decides whether the stage failed
+        let (primary, others): (Result<Forecast>, Vec<Result<Forecast>>) =
+            futures::future::join(
+                self.weather.forecast(location),
+                futures::future::join_all(
+                    self.config.locations
+                        .iter()
+                        .map(|other: &config::Location| self.weather.forecast(other))
+                )
+            ).await;
+        let elsewhere: Vec<Forecast> = self.config.locations
+            .iter()
+            .zip(others)
+            .filter_map(|(other, result)| match result {
+                Ok(weather) => Some(weather),
+                Err(e) => {
+                    console::warn(format!("Skipping weather for {}: {}", other.display_name(), e));
+                    None
+                }
+            })
+            .collect();
+        let weather: Option<Forecast> = match primary {
+            Ok(weather) => Some(weather),
+            Err(e) if policy.skip_weather => {
+                console::warn(format!("Skipping weather: {}", e));
+                degradations.push(Degradation::WeatherSkipped { reason: e.to_string() });
+                None
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        };
+
+        let saved: Option<FetchedContents> = match run {
+            Some(run) => run.load("contents.json")?,
+            None => None,
+        };
+        let FetchedContents { fetched: mut articles, failed } = match saved {
+            Some(contents) => {
+                console::status("Resuming: article content already fetched");
+                contents
+            }
+            None => {
+                console::status("Fetching article content...");
+                let (fetched, failed): (Vec<Article>, Vec<Article>) = self.fetch_contents(
+                    &filtered.kept
+                ).await;
+                let contents: FetchedContents = FetchedContents { fetched, failed };
+                if let Some(run) = run {
+                    run.save("contents.json", &contents)?;
+                }
+                contents
+            }
+        };
+        if !failed.is_empty() && policy.headline_only {
+            degradations.push(Degradation::HeadlineOnly { articles: failed.len() });
+            articles.extend(failed);
+        }
+
+        console::status(format!("Generating summary ({})...", self.summarizer.name()));
+        let script: BroadcastScript = match
+            self.summarizer.summarize(
+                weather.as_ref(),
+                &elsewhere,
+                &articles,
+                &self.config,
+                run
+            ).await
+        {
+            Ok(script) => script,
+            Err(e) if policy.extractive_fallback && e.is_llm() => {
+                console::warn(format!("Falling back to an extractive summary: {}", e));
+                degradations.push(Degradation::ExtractiveSummary { reason: e.to_string() });
+                ExtractiveSummarizer.summarize(
+                    weather.as_ref(),
+                    &elsewhere,
+                    &articles,
+                    &self.config,
+                    run
+                ).await?
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        };
+
+        Ok(RunReport {
+            generated_at: chrono::Local::now(),
+            language_code: self.config.language_code().to_string(),
+            weather,
+            elsewhere,
+            articles,
+            verdicts: filtered.verdicts,
+            script,
+            degradations,
+        })
+    }
+
+    /// Write the report to every sink and record the aired articles in the history,
+    /// failures are reported and skipped.
+    pub fn publish(&self, report: &RunReport) -> Vec<PathBuf> {
+        if let Some(history) = &self.history {
+            if let Err(e) = history.record_aired(&report.aired_articles(), &report.broadcast_id()) {
+                console::warn(format!("Failed to update the article history: {}", e));
+            }
+        }
+
+        let mut written: Vec<PathBuf> = Vec::new();
+        for sink in &self.sinks {
+            match sink.write(report, Path::new(&self.config.output_dir)) {
+                Ok(path) => {
+                    console::status(format!("Saved {}", path.display()));
+                    written.push(path);
+                }
+                Err(e) => {
+                    console::warn(format!("Failed to write {} output: {}", sink.extension(), e));
+                }
+            }
+        }
+        written
+    }
+
+    /// Speech, mixing and podcast publishing; returns the final audio file if any.
+    pub async fn speak(&self, script: &BroadcastScript) -> Option<PathBuf> {
+        let config: &Config = &self.config;
+        let tts_config: &config::TtsConfig = config.tts.as_ref()?;
+        let output_dir: &Path = Path::new(&config.output_dir);
+
+        console::status("Rendering speech...");
+        let engine: Box<dyn tts::TtsEngine> = tts::build_engine(tts_config, config, &self.client);
+        let rendered: tts::RenderedBroadcast = match
+            tts::render_broadcast(script, engine.as_ref(), &output_dir.join(&tts_config.output)).await
+        {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                console::warn(format!("Speech rendering failed: {}", e));
+                return None;
+            }
+        };
+        console::status(
+            format!(
+                "Broadcast saved to {} ({} segments)",
+                rendered.output.display(),
+                rendered.segments.len()
+            )
+        );
+
+        let mut broadcast_audio: PathBuf = rendered.output.clone();
+        if let Some(audio_config) = &config.audio {
+            console::status("Mixing broadcast...");
+            match audio::assemble(&rendered.segments, audio_config, output_dir).await {
+                Ok(output) => {
+                    console::status(format!("Mixed broadcast saved to {}", output.display()));
+                    broadcast_audio = output;
+                }
+                Err(e) => console::warn(format!("Audio mixing failed: {}", e)),
+            }
+        }
+
+        if let Some(podcast_config) = &config.podcast {
+            match output::podcast::publish_episode(&broadcast_audio, script, podcast_config, config) {
+                Ok(episode) =>
+                    console::status(format!("Podcast episode published to {}", episode.display())),
+                Err(e) => console::warn(format!("Podcast publishing failed: {}", e)),
+            }
+        }
+
+        Some(broadcast_audio)
+    }
+}
+
+fn open_verdict_cache(config: &Config) -> Option<Arc<VerdictCache>> {
+    let cache_config: &config::VerdictCacheConfig = &config.filter.cache;
+    if !cache_config.enabled {
+        return None;
+    }
+    let path: PathBuf = Path::new(&config.output_dir).join(&cache_config.path);
+    match VerdictCache::open(&path, cache_config.ttl_hours) {
+        Ok(cache) => Some(Arc::new(cache)),
+        Err(e) => {
+            console::warn(format!("AI filter cache disabled: {}", e));
+            None
+        }
+    }
+}
+
+/// Read a stage file (articles.json, kept.json, script.json).
+pub fn read_stage<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content: String = std::fs
+        ::read_to_string(path)
+        .map_err(|e| Error::Output(format!("failed to read {}: {}", path.display(), e)))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Write a stage file, creating its directory.
+pub fn write_stage<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(value)?)?;
+    console::status(format!("Saved {}", path.display()));
+    Ok(())
+}
@@ -0,0 +1,271 @@
+//! Cron-like broadcast schedules such as `07:00 weekdays Europe/Paris`.
+
+use chrono::{ DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday };
+use chrono_tz::Tz;
+use crate::error::{ Error, Result };
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Zone {
+    Local,
+    Named(Tz),
+}
+
+/// Times of day, days of the week and the time zone they are read in.
+///
+/// Written as `<times> [days] [time zone]`: times are `HH:MM` separated by commas; days are
+/// `daily` (the default), `weekdays`, `weekends`, or day names and ranges like `mon,wed,fri`
+/// or `mon-fri`; the time zone is an IANA name and defaults to the system's.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    times: Vec<NaiveTime>,
+    days: Vec<Weekday>,
+    zone: Zone,
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |reason: String| Error::Config(format!("invalid schedule '{}': {}", s, reason));
+        let mut tokens = s.split_whitespace();
+
+        let mut times: Vec<NaiveTime> = tokens
+            .next()
+            .ok_or_else(|| invalid("missing time".into()))?
+            .split(',')
+            .map(|t: &str| {
+                NaiveTime::parse_from_str(t, "%H:%M").map_err(|_| invalid(format!("bad time '{}'", t)))
+            })
+            .collect::<Result<_>>()?;
+        times.sort();
+        times.dedup();
+
+        let mut days: Option<Vec<Weekday>> = None;
+        let mut zone: Option<Zone> = None;
+        for token in tokens {
+            if let Ok(tz) = token.parse::<Tz>() {
+                if zone.replace(Zone::Named(tz)).is_some() {
+                    return Err(invalid("more than one time zone".into()));
+                }
+            } else if days.is_none() {
+                days = Some(parse_days(token).ok_or_else(|| invalid(format!("unknown days '{}'", token)))?);
+            } else {
+                return Err(invalid(format!("unexpected '{}'", token)));
+            }
+        }
+
+        Ok(Schedule {
+            times,
+            days: days.unwrap_or_else(|| WEEKDAYS.to_vec()),
+            zone: zone.unwrap_or(Zone::Local),
+        })
+    }
+}
+
+impl Schedule {
+    /// First scheduled instant strictly after `now`.
+    pub fn next_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today: NaiveDate = self.local_date(now);
+        // A week always holds at least one scheduled day
+        (0..=8)
+            .filter_map(|offset| today.checked_add_signed(Duration::days(offset)))
+            .flat_map(|date: NaiveDate| self.instants_on(date))
+            .find(|instant: &DateTime<Utc>| *instant > now)
+            .unwrap_or(now + Duration::days(1))
+    }
+
+    /// Last scheduled instant at or before `now`, within the past week.
+    pub fn last_at_or_before(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today: NaiveDate = self.local_date(now);
+        (0..=8)
+            .filter_map(|offset| today.checked_sub_signed(Duration::days(offset)))
+            .flat_map(|date: NaiveDate| self.instants_on(date).into_iter().rev())
+            .find(|instant: &DateTime<Utc>| *instant <= now)
+    }
+
+    /// The run owed at `now`: the last scheduled instant after `last`, earlier ones missed in
+    /// between are folded into it.
+    pub fn due(&self, last: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.last_at_or_before(now)
+            .filter(|due: &DateTime<Utc>| last.is_none_or(|last: DateTime<Utc>| *due > last))
+    }
+
+    fn local_date(&self, instant: DateTime<Utc>) -> NaiveDate {
+        match self.zone {
+            Zone::Local => instant.with_timezone(&Local).date_naive(),
+            Zone::Named(tz) => instant.with_timezone(&tz).date_naive(),
+        }
+    }
+
+    // Times skipped by a DST jump don't happen that day; repeated ones happen once
+    fn instants_on(&self, date: NaiveDate) -> Vec<DateTime<Utc>> {
+        if !self.days.contains(&date.weekday()) {
+            return Vec::new();
+        }
+        self.times
+            .iter()
+            .filter_map(|time: &NaiveTime| {
+                let local = date.and_time(*time);
+                match self.zone {
+                    Zone::Local => Local.from_local_datetime(&local).earliest().map(|t| t.to_utc()),
+                    Zone::Named(tz) => tz.from_local_datetime(&local).earliest().map(|t| t.to_utc()),
+                }
+            })
+            .collect()
+    }
+}
+
+fn parse_days(token: &str) -> Option<Vec<Weekday>> {
+    match token.to_lowercase().as_str() {
+        "daily" | "everyday" | "*" => {
+            return Some(WEEKDAYS.to_vec());
+        }
+        "weekdays" => {
+            return Some(WEEKDAYS[..5].to_vec());
+        }
+        "weekends" => {
+            return Some(WEEKDAYS[5..].to_vec());
+        }
+        _ => {}
+    }
+
+    let mut days: Vec<Weekday> = Vec::new();
+    for part in token.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let from: usize = from.parse::<Weekday>().ok()?.num_days_from_monday() as usize;
+                let to: usize = to.parse::<Weekday>().ok()?.num_days_from_monday() as usize;
+                // `fri-mon` wraps over the weekend
+                let mut i: usize = from;
+                loop {
+                    days.push(WEEKDAYS[i]);
+                    if i == to {
+                        break;
+                    }
+                    i = (i + 1) % 7;
+                }
+            }
+            None => days.push(part.parse::<Weekday>().ok()?),
+        }
+    }
+    days.dedup();
+    Some(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn schedule(s: &str) -> Schedule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_weekday_lists_and_ranges() {
+        assert_eq!(schedule("07:00 mon,wed,fri").days, [Weekday::Mon, Weekday::Wed, Weekday::Fri]);
+        assert_eq!(schedule("07:00 weekdays").days, WEEKDAYS[..5]);
+        assert_eq!(schedule("07:00 weekends").days, WEEKDAYS[5..]);
+        assert_eq!(schedule("07:00").days, WEEKDAYS);
+        assert_eq!(
+            schedule("07:00 fri-mon").days,
+            [Weekday::Fri, Weekday::Sat, Weekday::Sun, Weekday::Mon]
+        );
+        assert_eq!(
+            schedule("18:30,07:00,07:00 daily").times,
+            [NaiveTime::from_hms_opt(7, 0, 0).unwrap(), NaiveTime::from_hms_opt(18, 30, 0).unwrap()]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        for invalid in [
+            "",
+            "25:00",
+            "07:00 someday",
+            "07:00 mon Europe/Paris Europe/London",
+            "07:00 mon tue",
+        ] {
+            assert!(invalid.parse::<Schedule>().is_err(), "{:?} should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn next_run_skips_unscheduled_days() {
+        let schedule: Schedule = schedule("07:00,18:30 mon,wed,fri Europe/Paris");
+        // Tuesday noon in Paris: next is Wednesday 07:00 CEST
+        assert_eq!(
+            schedule.next_after(utc("2026-10-20T10:00:00Z")),
+            utc("2026-10-21T05:00:00Z")
+        );
+        // Wednesday 07:00 itself is past, the evening run comes next
+        assert_eq!(
+            schedule.next_after(utc("2026-10-21T05:00:00Z")),
+            utc("2026-10-21T16:30:00Z")
+        );
+    }
+
+    #[test]
+    fn next_run_follows_the_time_zone_across_dst() {
+        // Friday after the run, next is Monday, after the clocks went back on Sunday
+        let schedule: Schedule = schedule("07:00 weekdays Europe/Paris");
+        assert_eq!(
+            schedule.next_after(utc("2026-10-23T06:00:00Z")),
+            utc("2026-10-26T06:00:00Z")
+        );
+    }
+
+    #[test]
+    fn time_skipped_by_spring_forward_does_not_run() {
+        // 02:30 doesn't exist in Paris on 2026-03-29
+        let schedule: Schedule = schedule("02:30 daily Europe/Paris");
+        assert_eq!(
+            schedule.next_after(utc("2026-03-28T12:00:00Z")),
+            utc("2026-03-30T00:30:00Z")
+        );
+    }
+
+    #[test]
+    fn time_repeated_by_fall_back_runs_once() {
+        // 02:30 happens twice in Paris on 2026-10-25, only the first counts
+        let schedule: Schedule = schedule("02:30 daily Europe/Paris");
+        let first: DateTime<Utc> = schedule.next_after(utc("2026-10-24T12:00:00Z"));
+        assert_eq!(first, utc("2026-10-25T00:30:00Z"));
+        assert_eq!(schedule.next_after(first), utc("2026-10-26T01:30:00Z"));
+    }
+
+    #[test]
+    fn missed_run_is_due_once_after_wake_up() {
+        let schedule: Schedule = schedule("07:00 weekdays Europe/Paris");
+        let friday: DateTime<Utc> = utc("2026-10-23T05:00:00Z");
+        // Asleep over the weekend until Monday 08:30 in Paris: Monday's run is owed
+        let monday: DateTime<Utc> = utc("2026-10-26T07:30:00Z");
+        assert_eq!(schedule.due(Some(friday), monday), Some(utc("2026-10-26T06:00:00Z")));
+        // Once it ran, nothing is due until the next one
+        assert_eq!(schedule.due(Some(utc("2026-10-26T06:00:00Z")), monday), None);
+        // Several missed runs only owe the latest
+        let thursday: DateTime<Utc> = utc("2026-10-29T09:00:00Z");
+        assert_eq!(schedule.due(Some(friday), thursday), Some(utc("2026-10-29T06:00:00Z")));
+    }
+
+    #[test]
+    fn nothing_is_due_before_the_first_run() {
+        let schedule: Schedule = schedule("07:00 daily Europe/Paris");
+        let start: DateTime<Utc> = utc("2026-10-20T04:00:00Z");
+        assert_eq!(schedule.due(Some(start), utc("2026-10-20T04:30:00Z")), None);
+        assert_eq!(schedule.due(None, start), Some(utc("2026-10-19T05:00:00Z")));
+    }
+}
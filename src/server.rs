@@ -0,0 +1,210 @@
+//! Local HTTP server: the latest broadcast, its audio and feed, and a trigger for new runs.
+
+use std::{ net::SocketAddr, path::{ Path, PathBuf }, sync::{ Arc, Mutex } };
+use axum::{
+    extract::{ Query, State },
+    http::{ header, HeaderMap, StatusCode },
+    response::{ Html, IntoResponse, Response },
+    routing::{ get, post },
+    Json,
+    Router,
+};
+use chrono::{ DateTime, Local };
+use serde::{ Deserialize, Serialize };
+use crate::{
+    checkpoint::RunDir,
+    config::Config,
+    console::{ self, Progress },
+    daemon::{ self, ProfileLock },
+    error::{ Error, Result },
+    output::{ html::HtmlSink, podcast, RunReport, Sink },
+    pipeline::Pipeline,
+};
+
+/// The run in progress, or the outcome of the last one.
+#[derive(Debug, Serialize, Clone, Default)]
+struct RunStatus {
+    running: bool,
+    run_id: Option<String>,
+    started_at: Option<DateTime<Local>>,
+    finished_at: Option<DateTime<Local>>,
+    /// Set when the last run failed.
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    #[serde(flatten)]
+    run: RunStatus,
+    progress: Option<Progress>,
+}
+
+struct ServerState {
+    config: Config,
+    profile: Option<String>,
+    status: Mutex<RunStatus>,
+}
+
+#[derive(Deserialize)]
+struct LatestQuery {
+    format: Option<String>,
+}
+
+/// Serve until the process is stopped; `profile` is the one `POST /run` runs.
+pub async fn serve(config: Config, profile: Option<String>, addr: SocketAddr) -> Result<()> {
+    let state: Arc<ServerState> = Arc::new(ServerState {
+        config,
+        profile,
+        status: Mutex::new(RunStatus::default()),
+    });
+    let app: Router = Router::new()
+        .route("/latest", get(latest))
+        .route("/latest.mp3", get(latest_audio))
+        .route("/feed.xml", get(feed))
+        .route("/run", post(run))
+        .route("/status", get(status))
+        .with_state(state);
+
+    let listener: tokio::net::TcpListener = tokio::net::TcpListener
+        ::bind(addr).await
+        .map_err(|e| Error::Server(format!("failed to listen on {}: {}", addr, e)))?;
+    console::status(format!("Listening on http://{}", addr));
+    axum::serve(listener, app).await.map_err(|e| Error::Server(e.to_string()))
+}
+
+// Script of the newest run that got that far, as JSON or with the HTML sink
+async fn latest(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<LatestQuery>,
+    headers: HeaderMap
+) -> Response {
+    let report: RunReport = match latest_report(Path::new(&state.config.output_dir)) {
+        Ok(Some(report)) => report,
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, "No broadcast yet").into_response();
+        }
+        Err(e) => {
+            return internal_error(e);
+        }
+    };
+
+    let wants_html: bool = match query.format.as_deref() {
+        Some(format) => format == "html",
+        None =>
+            headers
+                .get(header::ACCEPT)
+                .and_then(|accept| accept.to_str().ok())
+                .is_some_and(|accept: &str| accept.contains("text/html")),
+    };
+    if wants_html {
+        match HtmlSink.render(&report) {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => internal_error(e),
+        }
+    } else {
+        Json(report).into_response()
+    }
+}
+
+async fn latest_audio(State(state): State<Arc<ServerState>>) -> Response {
+    let config: &Config = &state.config;
+    let output_dir: &Path = Path::new(&config.output_dir);
+    // The mixed broadcast when there is one, the bare speech otherwise
+    let candidates: Vec<PathBuf> = [
+        config.audio.as_ref().map(|audio| output_dir.join(&audio.output)),
+        config.tts.as_ref().map(|tts| output_dir.join(&tts.output)),
+    ]
+        .into_iter()
+        .flatten()
+        .collect();
+    match candidates.into_iter().find(|path: &PathBuf| path.is_file()) {
+        Some(path) => serve_file(&path).await,
+        None => (StatusCode::NOT_FOUND, "No audio yet").into_response(),
+    }
+}
+
+async fn feed(State(state): State<Arc<ServerState>>) -> Response {
+    match &state.config.podcast {
+        Some(podcast_config) => serve_file(&podcast::feed_path(podcast_config)).await,
+        None => (StatusCode::NOT_FOUND, "No `podcast` in the config").into_response(),
+    }
+}
+
+// Starts a run in the background; one at a time
+async fn run(State(state): State<Arc<ServerState>>) -> Response {
+    {
+        let mut status = state.status.lock().unwrap_or_else(|e| e.into_inner());
+        if status.running {
+            return (StatusCode::CONFLICT, Json(status.clone())).into_response();
+        }
+        *status = RunStatus {
+            running: true,
+            started_at: Some(Local::now()),
+            ..Default::default()
+        };
+    }
+    console::reset_progress();
+
+    let background: Arc<ServerState> = state.clone();
+    tokio::spawn(async move {
+        let outcome: Result<()> = run_broadcast(&background).await;
+        let mut status = background.status.lock().unwrap_or_else(|e| e.into_inner());
+        status.running = false;
+        status.finished_at = Some(Local::now());
+        if let Err(e) = outcome {
+            console::warn(format!("Run failed: {}", e));
+            status.error = Some(e.to_string());
+        }
+    });
+
+    let status: RunStatus = state.status.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    (StatusCode::ACCEPTED, Json(status)).into_response()
+}
+
+async fn run_broadcast(state: &ServerState) -> Result<()> {
+    let output_dir: &Path = Path::new(&state.config.output_dir);
+    let _lock: ProfileLock = daemon::lock_profile(output_dir, state.profile.as_deref())?;
+    let run: RunDir = RunDir::create(output_dir)?;
+    state.status.lock().unwrap_or_else(|e| e.into_inner()).run_id = Some(run.id().to_string());
+
+    let pipeline: Pipeline = Pipeline::builder(state.config.clone()).build()?;
+    let report: RunReport = pipeline.run_with(&run).await?;
+    pipeline.speak(&report.script).await;
+    Ok(())
+}
+
+async fn status(State(state): State<Arc<ServerState>>) -> Json<StatusResponse> {
+    let run: RunStatus = state.status.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let progress: Option<Progress> = run.running.then(console::progress);
+    Json(StatusResponse { run, progress })
+}
+
+fn latest_report(output_dir: &Path) -> Result<Option<RunReport>> {
+    for run in RunDir::list(output_dir)? {
+        if let Some(report) = run.load::<RunReport>("script.json")? {
+            return Ok(Some(report));
+        }
+    }
+    Ok(None)
+}
+
+async fn serve_file(path: &Path) -> Response {
+    let content_type: &str = match path.extension().and_then(|e| e.to_str()) {
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        Some("m4a") => "audio/mp4",
+        Some("xml") => "application/rss+xml",
+        _ => "application/octet-stream",
+    };
+    match tokio::fs::read(path).await {
+        Ok(bytes) => ([(header::CONTENT_TYPE, content_type)], bytes).into_response(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound =>
+            (StatusCode::NOT_FOUND, format!("{} not found", path.display())).into_response(),
+        Err(e) => internal_error(e.into()),
+    }
+}
+
+fn internal_error(e: Error) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+}
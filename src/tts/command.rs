@@ -0,0 +1,110 @@
+use std::{ path::Path, process::Stdio };
+use async_trait::async_trait;
+use tokio::{ io::AsyncWriteExt, process::Command };
+use crate::{ config::{ Config, TtsConfig }, error::{ Error, Result } };
+use super::TtsEngine;
+
+// Offline backends, the binary has to be on PATH
+
+pub struct Piper {
+    model: String,
+    length_scale: f32,
+}
+
+impl Piper {
+    pub fn new(tts: &TtsConfig, config: &Config) -> Self {
+        let model: String = tts.voice.clone().unwrap_or_else(||
+            (
+                match config.language_code() {
+                    "fr" => "fr_FR-siwis-medium",
+                    "de" => "de_DE-thorsten-medium",
+                    "es" => "es_ES-davefx-medium",
+                    "it" => "it_IT-riccardo-x_low",
+                    _ => "en_US-lessac-medium",
+                }
+            ).to_string()
+        );
+        Piper {
+            model,
+            // Piper stretches time, so a faster voice is a shorter length scale
+            length_scale: 1.0 / tts.speed.max(0.1),
+        }
+    }
+}
+
+#[async_trait]
+impl TtsEngine for Piper {
+    async fn synthesize(&self, text: &str, output: &Path) -> Result<()> {
+        let mut command: Command = Command::new("piper");
+        command
+            .arg("--model")
+            .arg(&self.model)
+            .arg("--length_scale")
+            .arg(self.length_scale.to_string())
+            .arg("--output_file")
+            .arg(output);
+        run_with_stdin(command, text).await
+    }
+}
+
+pub struct Espeak {
+    voice: String,
+    words_per_minute: u32,
+}
+
+impl Espeak {
+    pub fn new(tts: &TtsConfig, config: &Config) -> Self {
+        Espeak {
+            voice: tts.voice.clone().unwrap_or_else(|| config.language_code().to_string()),
+            // 175 wpm is espeak-ng's default rate
+            words_per_minute: (175.0 * tts.speed).round() as u32,
+        }
+    }
+}
+
+#[async_trait]
+impl TtsEngine for Espeak {
+    async fn synthesize(&self, text: &str, output: &Path) -> Result<()> {
+        let mut command: Command = Command::new("espeak-ng");
+        command
+            .arg("-v")
+            .arg(&self.voice)
+            .arg("-s")
+            .arg(self.words_per_minute.to_string())
+            .arg("-w")
+            .arg(output)
+            .arg("--stdin");
+        run_with_stdin(command, text).await
+    }
+}
+
+async fn run_with_stdin(mut command: Command, text: &str) -> Result<()> {
+    let program: String = command.as_std().get_program().to_string_lossy().into_owned();
+    let mut child: tokio::process::Child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Tts(format!("failed to start {}: {}", program, e)))?;
+
+    let mut stdin: tokio::process::ChildStdin = child.stdin
+        .take()
+        .ok_or_else(|| Error::Tts(format!("failed to open {} stdin", program)))?;
+    stdin.write_all(text.as_bytes()).await?;
+    drop(stdin);
+
+    let result: std::process::Output = child.wait_with_output().await?;
+    if !result.status.success() {
+        return Err(
+            Error::Tts(
+                format!(
+                    "{} exited with {}: {}",
+                    program,
+                    result.status,
+                    String::from_utf8_lossy(&result.stderr).trim()
+                )
+            )
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+pub mod command;
+pub mod openai;
+
+use std::path::{ Path, PathBuf };
+use async_trait::async_trait;
+use reqwest::Client;
+use crate::{
+    ai::script::BroadcastScript,
+    config::{ Config, TtsBackend, TtsConfig },
+    error::{ Error, Result },
+};
+
+#[async_trait]
+pub trait TtsEngine: Send + Sync {
+    // Render `text` as a WAV file at `output`
+    async fn synthesize(&self, text: &str, output: &Path) -> Result<()>;
+}
+
+pub struct RenderedBroadcast {
+    pub segments: Vec<PathBuf>,
+    pub output: PathBuf,
+}
+
+pub fn build_engine(tts: &TtsConfig, config: &Config, client: &Client) -> Box<dyn TtsEngine> {
+    match tts.backend {
+        TtsBackend::Piper => Box::new(command::Piper::new(tts, config)),
+        TtsBackend::Espeak => Box::new(command::Espeak::new(tts, config)),
+        TtsBackend::Openai => Box::new(openai::OpenAiTts::new(tts, config, client.clone())),
+    }
+}
+
+// Render every segment of the script, then join them into one file at `output`
+pub async fn render_broadcast(
+    script: &BroadcastScript,
+    engine: &dyn TtsEngine,
+    output: &Path
+) -> Result<RenderedBroadcast> {
+    let segments_dir: PathBuf = output.with_extension("segments");
+    tokio::fs::create_dir_all(&segments_dir).await?;
+
+    let mut segments: Vec<PathBuf> = Vec::new();
+    for (i, text) in script.spoken_segments().iter().enumerate() {
+        let path: PathBuf = segments_dir.join(format!("{:02}.wav", i));
+        engine.synthesize(text, &path).await?;
+        segments.push(path);
+    }
+
+    concat_wav(&segments, output)?;
+
+    Ok(RenderedBroadcast {
+        segments,
+        output: output.to_path_buf(),
+    })
+}
+
+fn concat_wav(inputs: &[PathBuf], output: &Path) -> Result<()> {
+    concat_samples(inputs, output).map_err(|e| Error::Tts(e.to_string()))
+}
+
+fn concat_samples(inputs: &[PathBuf], output: &Path) -> std::result::Result<(), String> {
+    let wav_error = |path: &Path, e: hound::Error| format!("{}: {}", path.display(), e);
+    let first: &PathBuf = inputs.first().ok_or("no segment to concatenate")?;
+    let spec: hound::WavSpec = hound::WavReader
+        ::open(first)
+        .map_err(|e| wav_error(first, e))?
+        .spec();
+    let mut writer: hound::WavWriter<_> = hound::WavWriter
+        ::create(output, spec)
+        .map_err(|e| wav_error(output, e))?;
+
+    for input in inputs {
+        let mut reader: hound::WavReader<_> = hound::WavReader
+            ::open(input)
+            .map_err(|e| wav_error(input, e))?;
+        if reader.spec() != spec {
+            return Err(format!("{} does not match the format of {}", input.display(), first.display()));
+        }
+        match spec.sample_format {
+            hound::SampleFormat::Int => {
+                for sample in reader.samples::<i32>() {
+                    let sample: i32 = sample.map_err(|e| wav_error(input, e))?;
+                    writer.write_sample(sample).map_err(|e| wav_error(output, e))?;
+                }
+            }
+            hound::SampleFormat::Float => {
+                for sample in reader.samples::<f32>() {
+                    let sample: f32 = sample.map_err(|e| wav_error(input, e))?;
+                    writer.write_sample(sample).map_err(|e| wav_error(output, e))?;
+                }
+            }
+        }
+    }
+
+    writer.finalize().map_err(|e| wav_error(output, e))
+}
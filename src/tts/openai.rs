@@ -0,0 +1,66 @@
+use std::path::Path;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+use crate::{ config::{ Config, TtsConfig }, error::{ Error, Result } };
+use super::TtsEngine;
+
+// Any server implementing OpenAI's `/audio/speech` endpoint
+pub struct OpenAiTts {
+    url: String,
+    api_key: String,
+    model: String,
+    voice: String,
+    speed: f32,
+    client: Client,
+}
+
+impl OpenAiTts {
+    pub fn new(tts: &TtsConfig, config: &Config, client: Client) -> Self {
+        let url: String = tts.api_url
+            .clone()
+            .unwrap_or_else(|| config.api_url.replace("/chat/completions", "/audio/speech"));
+        OpenAiTts {
+            url,
+            api_key: tts.api_key.clone().unwrap_or_else(|| config.api_key.clone()),
+            model: tts.model.clone(),
+            // Voices are multilingual, the language follows the text itself
+            voice: tts.voice.clone().unwrap_or_else(|| "alloy".into()),
+            speed: tts.speed,
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl TtsEngine for OpenAiTts {
+    async fn synthesize(&self, text: &str, output: &Path) -> Result<()> {
+        let response: reqwest::Response = self.client
+            .post(&self.url)
+            .header("Authorization", format!("Bearer {}", &self.api_key))
+            .json(
+                &json!({
+                "model": self.model,
+                "input": text,
+                "voice": self.voice,
+                "speed": self.speed,
+                "response_format": "wav"
+            })
+            )
+            .send().await
+            .map_err(|e| Error::Tts(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status: reqwest::StatusCode = response.status();
+            let error_text: String = response.text().await.unwrap_or_default();
+            return Err(Error::Tts(format!("API error: {} - {}", status, error_text)));
+        }
+
+        let audio: Vec<u8> = response
+            .bytes().await
+            .map_err(|e| Error::Tts(e.to_string()))?
+            .to_vec();
+        tokio::fs::write(output, audio).await?;
+        Ok(())
+    }
+}